@@ -0,0 +1,33 @@
+//! Measures the cost of initializing (and resetting) a magic static, to compare the default `SeqCst` orderings against the `relaxed-init` feature flag.
+//!
+//! ```text
+//! cargo bench -p magic_static --bench init --no-default-features
+//! cargo bench -p magic_static --bench init --no-default-features --features relaxed-init
+//! ```
+//!
+//! With `std`, finishing an initialization also checks whether any thread is waiting to be notified, which is all it should cost when none is:
+//!
+//! ```text
+//! cargo bench -p magic_static --bench init
+//! ```
+
+use magic_static::MagicStatic;
+use std::{hint::black_box, time::Instant};
+
+const ITERATIONS: u32 = 10_000_000;
+
+static MAGIC: MagicStatic<usize> = MagicStatic::new(|| black_box(42));
+
+fn main() {
+	let start = Instant::now();
+	for _ in 0..ITERATIONS {
+		magic_static::init! { MAGIC };
+		black_box(*MAGIC);
+		unsafe { MAGIC.reset() };
+	}
+	let elapsed = start.elapsed();
+
+	let orderings = if cfg!(feature = "relaxed-init") { "Acquire/Release" } else { "SeqCst" };
+	let std = if cfg!(feature = "std") { "with" } else { "without" };
+	println!("{} orderings, {} std: {:?} per initialization ({} iterations)", orderings, std, elapsed / ITERATIONS, ITERATIONS);
+}
\ No newline at end of file
@@ -0,0 +1,63 @@
+use std::{env::VarError, fmt::Display, str::FromStr, sync::RwLock};
+
+/// A function called with the name of an environment variable, its value and the error that parsing it failed with.
+pub type EnvWarningHook = fn(variable: &'static str, value: &str, error: &dyn Display);
+
+static WARNING_HOOK: RwLock<Option<EnvWarningHook>> = RwLock::new(None);
+
+/// Sets the function called when a magic static declared with `static env ref` can't parse the value of its environment variable, just before it falls
+/// back to its default value. Without one, magic statics fall back to their default value silently.
+///
+/// An environment variable whose value isn't valid unicode counts as failing to parse, and is passed to the hook lossily converted.
+///
+/// # Example
+///
+/// ```rust
+/// # #[macro_use] extern crate magic_static;
+/// magic_statics! {
+///     static env ref WORKERS: usize = 4;
+/// }
+///
+/// fn main() {
+///     magic_static::set_env_warning_hook(|variable, value, error| {
+///         eprintln!("warning: ignoring ${}={:?} ({}), using the default instead", variable, value, error);
+///     });
+///
+///     magic_static::init! { WORKERS };
+/// }
+/// ```
+pub fn set_env_warning_hook(hook: EnvWarningHook) {
+	*WARNING_HOOK.write().unwrap_or_else(|poisoned| poisoned.into_inner()) = Some(hook);
+}
+
+fn warn(variable: &'static str, value: &str, error: &dyn Display) {
+	let hook = *WARNING_HOOK.read().unwrap_or_else(|poisoned| poisoned.into_inner());
+	if let Some(hook) = hook {
+		hook(variable, value, error);
+	}
+}
+
+#[doc(hidden)]
+/// The initializer of a magic static declared with `static env ref`.
+pub fn __from_env<T>(variable: &'static str, default: fn() -> T) -> T
+where
+	T: FromStr,
+	T::Err: Display,
+{
+	match std::env::var(variable) {
+		Ok(value) => match value.parse() {
+			Ok(value) => value,
+			Err(error) => {
+				warn(variable, &value, &error);
+				default()
+			},
+		},
+
+		Err(error) => {
+			if let VarError::NotUnicode(value) = &error {
+				warn(variable, &value.to_string_lossy(), &error);
+			}
+			default()
+		},
+	}
+}
\ No newline at end of file
@@ -9,6 +9,26 @@
 //! magic_static = "*"
 //! ```
 //!
+//! ### `std`
+//!
+//! Enabled by default. Threads waiting for another thread to initialize a magic static sleep until it is initialized instead of spinning, and debug builds detect magic statics that are accessed during the initialization of another magic static before being initialized themselves. It also provides thread-local magic statics with `thread_magic_statics!`.
+//!
+//! To use `magic_static` in a `no_std` environment, disable the default features:
+//!
+//! ```toml
+//! [dependencies]
+//! magic_static = { version = "*", default-features = false }
+//! ```
+//!
+//! ### `alloc`
+//!
+//! For `no_std` targets with an allocator, this enables the parts of `magic_static` which only need `alloc` rather than `std`: `magic_static::deinit_all!()`, and `magic_static::uninitialized_names()` with `auto-register`. Their global state is guarded by a critical section (using [`critical-section`](https://crates.io/crates/critical-section)) instead of a `Mutex`, so your target must provide a `critical-section` implementation. This has no effect with `std`.
+//!
+//! ```toml
+//! [dependencies]
+//! magic_static = { version = "*", default-features = false, features = ["alloc"] }
+//! ```
+//!
 //! ### `bare-metal`
 //!
 //! If your target doesn't support atomics or threads, enable the `bare-metal` feature flag in your `Cargo.toml`:
@@ -18,6 +38,142 @@
 //! magic_static = { version = "*", features = ["bare-metal"] }
 //! ```
 //!
+//! ### `critical-section`
+//!
+//! Implies `bare-metal`, and initializes magic statics inside a critical section (using [`critical-section`](https://crates.io/crates/critical-section)), so that interrupt handlers can't observe a magic static which is in the middle of being initialized. Your target must provide a `critical-section` implementation.
+//!
+//! **Initializers then run with interrupts disabled**, so they should be kept short.
+//!
+//! ```toml
+//! [dependencies]
+//! magic_static = { version = "*", features = ["critical-section"] }
+//! ```
+//!
+//! ### `checked-release`
+//!
+//! Keeps the check that a magic static has been initialized before its value is accessed in release builds, so that accessing an uninitialized magic static panics instead of being undefined behaviour. Every access then pays for the check, with or without `bare-metal`.
+//!
+//! To keep (or drop) the check for a single magic static instead, declare it with `#[check = always]` (or `#[check = never]`) in `magic_statics!`, or `#[magic_static(check = always)]`.
+//!
+//! ```toml
+//! [dependencies]
+//! magic_static = { version = "*", features = ["checked-release"] }
+//! ```
+//!
+//! ### `unsafe-mut`
+//!
+//! Enables mutable access to the values of magic statics (e.g. `DerefMut`.)
+//!
+//! **This is only sound in single-threaded applications**, as magic statics are always `Sync` and nothing synchronizes the mutation.
+//!
+//! ```toml
+//! [dependencies]
+//! magic_static = { version = "*", features = ["unsafe-mut"] }
+//! ```
+//!
+//! ### `profiling`
+//!
+//! Records how long each magic static takes to initialize, which can be retrieved with `magic_static::init_timings()`. Requires `std`.
+//!
+//! ```toml
+//! [dependencies]
+//! magic_static = { version = "*", features = ["profiling"] }
+//! ```
+//!
+//! ### `spin-timeout`
+//!
+//! If a thread tries to initialize a magic static while another thread is initializing it, it waits for the other thread to finish. With this feature flag, a waiting thread panics instead if initialization does not complete within `magic_static::SPIN_TIMEOUT` (30 seconds), or the number of seconds in the `MAGIC_STATIC_SPIN_TIMEOUT` environment variable. This makes deadlocks easier to debug. Requires `std`, and does nothing with `bare-metal`.
+//!
+//! ```toml
+//! [dependencies]
+//! magic_static = { version = "*", features = ["spin-timeout"] }
+//! ```
+//!
+//! ### `auto-register`
+//!
+//! Registers every magic static in a global registry (using [`inventory`](https://crates.io/crates/inventory)) so that they can all be initialized at once with `magic_static::init_all!()`, without listing them. With `std` or `alloc`, `magic_static::uninitialized_names()` also lists the magic statics which haven't been initialized. `magic_static::initialized_count()` counts how many of them have been initialized, e.g. for a readiness probe.
+//!
+//! ```toml
+//! [dependencies]
+//! magic_static = { version = "*", features = ["auto-register"] }
+//! ```
+//!
+//! ### `startup`
+//!
+//! Implies `auto-register`, and initializes every magic static automatically before `main` runs (using [`ctor`](https://crates.io/crates/ctor)), so you don't need to initialize them yourself.
+//!
+//! **The order in which magic statics are initialized is then undefined**, so a magic static must not access another magic static during its initialization.
+//!
+//! ```toml
+//! [dependencies]
+//! magic_static = { version = "*", features = ["startup"] }
+//! ```
+//!
+//! ### `serde`
+//!
+//! Implements `Serialize` for magic statics, which serializes their value. `Deserialize` is implemented too, for magic statics that aren't `static`s (e.g. in tests.)
+//!
+//! ```toml
+//! [dependencies]
+//! magic_static = { version = "*", features = ["serde"] }
+//! ```
+//!
+//! ### `drop-at-exit`
+//!
+//! Drops the values of magic statics when the program exits (using `atexit`), **in the reverse order they were initialized**, so that a magic static is dropped before the magic statics it accessed during its initialization. Without this, the values of magic statics are never dropped, like any `static`. Requires `std`.
+//!
+//! Magic statics must not be accessed once the program has started exiting (e.g. by threads which are still running), and thread-local magic statics are dropped with their thread instead.
+//!
+//! ```toml
+//! [dependencies]
+//! magic_static = { version = "*", features = ["drop-at-exit"] }
+//! ```
+//!
+//! ### `verify-init`
+//!
+//! Implies `auto-register` and `std`. In debug builds, `#[magic_static::main]` on your `main` function checks that every magic static (except lazy magic statics) has been initialized once it has initialized the magic statics it lists, and panics naming those which haven't. This catches magic statics which were never added to `main` at startup, before they can cause undefined behaviour in release builds.
+//!
+//! ```toml
+//! [dependencies]
+//! magic_static = { version = "*", features = ["verify-init"] }
+//! ```
+//!
+//! ### `require-sync`
+//!
+//! Unless `single-thread` is enabled, `MagicStatic` is `Sync` whatever its value, so nothing stops a magic static holding a type which isn't thread-safe (e.g. a `Cell`) from being accessed by multiple threads. With this feature, magic statics declared with `magic_statics!` or `#[magic_static]` fail to compile unless their type is `Sync`, which is useful for multi-threaded applications.
+//!
+//! ```toml
+//! [dependencies]
+//! magic_static = { version = "*", features = ["require-sync"] }
+//! ```
+//!
+//! ### `relaxed-init`
+//!
+//! Initializing a magic static claims it with a compare-exchange and then publishes its value with a store, which are `SeqCst` by default. With this feature, the compare-exchange uses `Acquire` (on success and failure) and the store uses `Release`, which is still enough for every thread that observes the magic static as initialized to see its value, and is cheaper on most platforms. The `init` benchmark compares the two (`cargo bench -p magic_static --bench init --no-default-features`, with and without `--features relaxed-init`.) Has no effect with `bare-metal`.
+//!
+//! ```toml
+//! [dependencies]
+//! magic_static = { version = "*", features = ["relaxed-init"] }
+//! ```
+//!
+//! ### `parallel-init`
+//!
+//! Implies `std`. Provides `magic_static::init_parallel!`, which initializes magic statics on multiple threads at once to speed up startup when their initializers are slow. It can't be used with `bare-metal`.
+//!
+//! ```toml
+//! [dependencies]
+//! magic_static = { version = "*", features = ["parallel-init"] }
+//! ```
+//!
+//! ### `single-thread`
+//!
+//! Makes `MagicStatic` only `Sync` if its value is, so that a magic static holding a type which isn't thread-safe (e.g. a `Cell`) can't be shared between threads. As a `static` must be `Sync`, such magic statics fail to compile, just like with `require-sync`, but so does any other code which would share one between threads. Thread magic statics (see `thread_magic_statics!`) can still hold any type.
+//!
+//! ```toml
+//! [dependencies]
+//! magic_static = { version = "*", features = ["single-thread"] }
+//! ```
+//!
 //! ## Example
 //!
 //! ```rust
@@ -78,34 +234,168 @@
 //! `magic_static` only performs these checks in debug builds, making it a more ergonomic choice for single-threaded and performance-critical applications.
 //!
 //! The downside of using `magic_static` is that you must manually initialize each `magic_static` in your `main` function or somewhere appropriate. See above for an example.
+//!
+//! ## Upgrading from 3.x
+//!
+//! `magic_static::init!` now evaluates to the number of magic statics it initialized (a `usize`), so an `init!` at the end of a block which must evaluate to `()` (such as a function returning nothing) needs a semicolon: `magic_static::init! { MAGIC };`.
+//!
+//! The fields of `MagicStatic` are no longer public, and the hidden `__magic_static_initialized!` macro is gone. Construct magic statics with `MagicStatic::new` (or one of its siblings) instead of a struct literal.
 
 #![allow(clippy::needless_doctest_main)]
 #![no_std]
 
+#[cfg(feature = "std")]
+extern crate std;
+
+#[cfg(any(feature = "std", feature = "alloc"))]
+extern crate alloc;
+
 pub use magic_static_macro::{main, magic_static};
 
 #[doc(hidden)]
 pub mod private;
 
-#[doc(hidden)]
-pub use private::*;
+pub use private::{MagicStatic, ConstMagicStatic, LazyMagicStatic, CheckedMagicStatic, AlreadyInitialized, Poisoned, InitState, MagicStaticInit, Aligned, init_many};
+
+#[cfg(all(feature = "auto-register", any(feature = "std", feature = "alloc")))]
+pub use private::uninitialized_names;
+
+#[cfg(feature = "auto-register")]
+pub use private::initialized_count;
+
+#[cfg(feature = "profiling")]
+mod profiling;
+
+#[cfg(feature = "profiling")]
+pub use profiling::init_timings;
+
+#[cfg(all(feature = "parallel-init", feature = "bare-metal"))]
+compile_error!("The `parallel-init` feature can't be used with `bare-metal`, which isn't thread-safe");
+
+#[cfg(feature = "std")]
+mod env;
+
+#[cfg(feature = "std")]
+pub use env::{set_env_warning_hook, EnvWarningHook};
+
+#[cfg(feature = "std")]
+mod thread_local;
+
+#[cfg(feature = "std")]
+pub use thread_local::ThreadMagicStatic;
+
+#[cfg(all(feature = "std", not(feature = "bare-metal")))]
+mod wait;
+
+#[cfg(any(feature = "std", feature = "alloc"))]
+mod teardown;
+
+#[cfg(feature = "spin-timeout")]
+mod spin_timeout;
+
+#[cfg(feature = "spin-timeout")]
+pub use spin_timeout::SPIN_TIMEOUT;
 
 #[macro_export]
 /// Defines new magic statics.
 ///
 /// Magic statics are initialized manually using the `magic_static::init!` macro or `magic_static::main` attribute macro.
 ///
+/// Initializers are turned into function pointers (`fn() -> T`) which are called when the magic static is initialized, so like the initializer of any
+/// `static`, they can't capture local variables, even if the magic statics are declared inside a function. Referring to one fails with "attempt to
+/// use a non-constant value in a constant" (E0435): rather than following its suggestion to use `let`, move the value into a `const` or into a magic
+/// static of its own. This crate can't replace that error with a clearer one of its own, as rustc reports it while resolving names, before any
+/// assertion generated by the macro (such as `const _: fn() -> T = || ...;`) is checked, which would only report it a second time.
+///
+/// ```rust,compile_fail,E0435
+/// # #[macro_use] extern crate magic_static;
+/// fn main() {
+///     let answer = 42;
+///
+///     magic_statics! {
+///         static ref ANSWER: u32 = answer;
+///     }
+/// }
+/// ```
+///
+/// Attributes (such as `#[cfg]`, doc comments, `#[allow(...)]` or `#[deprecated]`) on each magic static are kept on the generated `static`. The
+/// code generated to register and initialize a `#[deprecated]` magic static doesn't count as a use of it, so only accessing it warns:
+///
+/// ```rust
+/// # #![deny(deprecated, non_upper_case_globals)]
+/// # #[macro_use] extern crate magic_static;
+/// magic_statics! {
+///     #[allow(non_upper_case_globals)]
+///     static ref camelCase: usize = 1;
+///
+///     #[deprecated(note = "use `NEW` instead")]
+///     static ref OLD: usize = 2;
+///
+///     static ref NEW: usize = 2;
+/// }
+///
+/// # fn main() {
+/// magic_static::init! { camelCase, NEW };
+/// assert_eq!(*camelCase + *NEW, 3);
+/// # }
+/// ```
+///
+/// ```compile_fail
+/// # #![deny(deprecated)]
+/// # #[macro_use] extern crate magic_static;
+/// magic_statics! {
+///     #[deprecated(note = "use `NEW` instead")]
+///     static ref OLD: usize = 2;
+/// }
+///
+/// # fn main() {
+/// assert_eq!(*OLD, 2); // error: use of deprecated static `OLD`
+/// # }
+/// ```
+///
+/// Magic statics declared with `static try ref NAME: Result<T, E> = ...;` have a fallible initializer, which can use the `?` operator. They dereference to `T`
+/// and can be initialized with `magic_static::init_try!` to handle the error.
+///
+/// Magic statics declared with `const ref NAME: T = ...;` have a constant value, and are [`ConstMagicStatic`]s: they are always initialized,
+/// so accessing their value never checks anything, even in debug builds.
+///
+/// Magic statics declared with `lazy static ref NAME: T = ...;` are [`LazyMagicStatic`]s: if they haven't been initialized when their value is accessed,
+/// they are initialized then, like a lazily initialized static. This means **every access pays for a check**.
+///
+/// Magic statics declared with `#[check = always]` or `#[check = never]` are [`CheckedMagicStatic`]s: accessing their value always or never checks whether
+/// they have been initialized, whatever the build profile (and the `checked-release` feature.) This works with `static ref`, `static async ref` and
+/// `static try ref`.
+///
+/// Magic statics declared with `static async ref NAME: T = ...;` have an async initializer, which can use `.await`. They must be initialized by an `async`
+/// function annotated with `#[magic_static::main]`, and require the `std` feature.
+///
+/// Magic statics declared with `static env ref NAME: T = ...;` are initialized by parsing the environment variable `NAME` with `T`'s `FromStr`
+/// implementation, falling back to the expression if it isn't set or fails to parse (see [`set_env_warning_hook`].) The variable can be named
+/// explicitly with `static env("VARIABLE") ref`. They require the `std` feature.
+///
+/// The initializer of any magic static can be chosen by a `cfg` predicate with `= #[cfg(...)] { ... } else { ... };`: the first block is used if the
+/// predicate holds, and the second one otherwise (e.g. `static ref BACKEND: Backend = #[cfg(feature = "gpu")] { Gpu::new() } else { Cpu::new() };`.)
+///
+/// A magic static can declare the magic statics it accesses during its initialization with `#[depends_on(...)]`. These will always be
+/// initialized before it, and a dependency cycle will panic when the magic static is initialized.
+///
+/// Without `#[depends_on(...)]`, a magic static can still access the magic statics declared before it, as long as they are initialized in
+/// declaration order, like `magic_statics_mod!` does (e.g. `static ref B: usize = *A + 1;` after `static ref A: usize = 1;`.)
+///
+/// With the `auto-register` feature, `#[group = "name"]` adds a magic static to a group which can be initialized with `magic_static::init_group!("name")`.
+///
 /// # Safety
 ///
 /// The following behaviour is considered undefined:
 ///
-/// * Initializing magic statics from multiple threads concurrently.
+/// * Initializing magic statics from multiple threads concurrently, except with `magic_static::init_parallel!` or by accessing lazy magic statics.
 /// * Spawning new threads and accessing magic statics during initialization from them.
 /// * Interior mutability of magic statics where the mutability is not synchronized across multiple threads (e.g. with a Mutex or RwLock.) This is not a problem for single-threaded applications.
 ///
 /// # Example
 ///
 /// ```rust
+/// # #[macro_use] extern crate magic_static;
 /// mod foo {
 ///     magic_statics! {
 ///         pub(super) static ref MAGIC: usize = {
@@ -114,6 +404,10 @@ pub use private::*;
 ///         };
 ///
 ///         pub(super) static ref BAR: std::sync::Mutex<()> = std::sync::Mutex::new(());
+///
+///         // `MAGIC` will be initialized before `DOUBLE_MAGIC`
+///         #[depends_on(MAGIC)]
+///         pub(super) static ref DOUBLE_MAGIC: usize = *MAGIC * 2;
 ///     }
 /// }
 ///
@@ -141,8 +435,8 @@ pub use private::*;
 /// #[magic_static::main(
 ///     FOO_BAR,
 ///
-///     foo::MAGIC,
 ///     foo::BAR,
+///     foo::DOUBLE_MAGIC, // This will initialize `foo::MAGIC` first
 ///
 ///     mod baz // This will initialize all magic statics in the `baz` module
 /// )]
@@ -151,147 +445,1059 @@ pub use private::*;
 /// }
 /// ```
 macro_rules! magic_statics {
-	{ $($vis:vis static $ident:ident: $ty:ty = $expr:expr;)* } => {
-		compile_error!("Expected `static ref`, got `static`")
+	{ $($(#[$($attr:tt)*])* $vis:vis static ref $ident:ident: $ty:ty = $expr:expr;)* } => {
+		$($crate::__magic_statics_item! { [] [] [] [] [$(#[$($attr)*])*] [$vis] $ident ref [$ty] [$expr] })*
 	};
 
-	{ $($vis:vis static mut $ident:ident: $ty:ty = $expr:expr;)* } => {
-		compile_error!("Expected `static ref`, got `static mut`")
-	};
-
-	{ $($vis:vis static ref $ident:ident: $ty:ty = $expr:expr;)* } => {
-		$(
-			$vis static $ident: $crate::MagicStatic<$ty> = $crate::MagicStatic {
-				initialized: $crate::__magic_static_initialized!(),
-				value: ::core::cell::UnsafeCell::new(::core::mem::MaybeUninit::uninit()),
-				init: || $expr
-			};
-		)*
+	{ $($tt:tt)* } => {
+		$crate::__magic_statics_parse! { __magic_statics_items [] [] $($tt)* }
 	};
 }
 
 #[macro_export]
-/// The same as `magic_static!` but automatically generates the module-level `magic_static` function for you:
-///
-/// **You can only have one of these per module (scope)** - if you want to initialize magic statics in a group, define a `magic_static` function in your module yourself! (See the example)
-///
-/// # Example
-///
-/// ```rust
-/// mod foo {
-///     // Note the use of `magic_statics_mod!` rather than `magic_static!` here
-///     magic_statics_mod! {
-///         pub(super) static ref MAGIC: usize = {
-///             println!("Magic!");
-///             42
-///         };
-///
-///         pub(super) static ref BAR: std::sync::Mutex<()> = std::sync::Mutex::new(());
-///     }
-///
-///     // Will generate the following:
-///     /*
-///     #[magic_static::main(
-///         MAGIC,
-///         BAR
-///     )]
-///     pub fn magic_static() {}
-///     */
-/// }
-///
-/// #[magic_static::main(
-///     mod foo // This will initialize all magic statics in `foo`
-/// )]
-/// fn main() {
-///     println!("Hello, world!");
-/// }
-/// ```
-macro_rules! magic_statics_mod {
-	{ $($vis:vis static ref $ident:ident: $ty:ty = $expr:expr;)* } => {
-		$crate::magic_statics!($($vis static ref $ident: $ty = $expr;)*);
+#[doc(hidden)]
+/// Parses the declarations of `magic_statics!` one at a time into `{ [attrs] [vis] IDENT kind [...] [expr] }` entries, and passes them to `$callback`.
+macro_rules! __magic_statics_parse {
+	($callback:ident [$($args:tt)*] [$($entries:tt)*]) => {
+		$crate::$callback! { $($args)* $($entries)* }
+	};
 
-		#[doc(hidden)]
-		#[inline]
-		pub fn magic_static() {
-			$crate::init! {
-				$($ident),*
-			}
+	($callback:ident $args:tt $entries:tt $(#[$($attr:tt)*])* $vis:vis static ref $ident:ident: $ty:ty = #[cfg($($cfg:tt)*)] $primary:block else $fallback:block; $($tail:tt)*) => {
+		$crate::__magic_statics_parse! {
+			$callback $args $entries
+			#[cfg($($cfg)*)] $(#[$($attr)*])* $vis static ref $ident: $ty = $primary;
+			#[cfg(not($($cfg)*))] $(#[$($attr)*])* $vis static ref $ident: $ty = $fallback;
+			$($tail)*
 		}
 	};
-}
 
-#[macro_export]
-/// Manually initializes the provided magic statics **in the specified order**.
-///
-/// Does nothing to a magic static if it has already been initialized.
-///
-/// # Safety
-///
-/// The following behaviour is considered undefined:
-///
-/// * Initializing magic statics from multiple threads concurrently.
-/// * Spawning new threads and accessing magic statics during initialization from them.
-/// * Interior mutability of magic statics where the mutability is not synchronized across multiple threads (e.g. with a Mutex or RwLock.) This is not a problem for single-threaded applications.
-///
-/// # Example
-///
-/// ```rust
-/// # #[macro_use] extern crate r#magic_static;
-/// mod foo {
-///     magic_static! {
-///         pub(super) static ref BAR: std::sync::Mutex<()> = std::sync::Mutex::new(());
-///         pub(super) static ref MAGIC: usize = {
-///             println!("Magic!");
-///             42
-///         };
-///     }
-/// }
-///
-/// // You can also modularize your magic statics like so:
-/// mod baz {
-///     magic_static! {
-///         pub(super) static ref MAGIC: usize = {
-///             println!("Magic!");
-///             42
-///         };
-///
-///         pub(super) static ref BAR: std::sync::Mutex<()> = std::sync::Mutex::new(());
-///     }
-///
-///     #[magic_static::main(
-///         MAGIC,
-///         BAR
-///     )]
-///     // Must be called `magic_static`
-///     pub fn magic_static() {}
-/// }
-///
-/// fn main() {
-///     magic_static::init! {
-///         foo::BAR,
-///         foo::MAGIC,
-///         mod baz // This will initialize all magic statics in `baz`
-///     }
-/// }
-/// ```
-macro_rules! init {
-	() => {};
+	($callback:ident $args:tt [$($entries:tt)*] $(#[$($attr:tt)*])* $vis:vis static ref $ident:ident: $ty:ty = $expr:expr; $($tail:tt)*) => {
+		$crate::__magic_statics_parse! { $callback $args [$($entries)* { [$(#[$($attr)*])*] [$vis] $ident ref [$ty] [$expr] }] $($tail)* }
+	};
 
-	(mod $($path:ident)::+) => {
-		$($path)::+::magic_static()
+	($callback:ident $args:tt $entries:tt $(#[$($attr:tt)*])* $vis:vis lazy static ref $ident:ident: $ty:ty = #[cfg($($cfg:tt)*)] $primary:block else $fallback:block; $($tail:tt)*) => {
+		$crate::__magic_statics_parse! {
+			$callback $args $entries
+			#[cfg($($cfg)*)] $(#[$($attr)*])* $vis lazy static ref $ident: $ty = $primary;
+			#[cfg(not($($cfg)*))] $(#[$($attr)*])* $vis lazy static ref $ident: $ty = $fallback;
+			$($tail)*
+		}
 	};
 
-	(mod $($path:ident)::+, $($tail:tt)*) => {{
-		$($path)::+::magic_static();
-		$crate::init!($($tail)*);
-	}};
+	($callback:ident $args:tt [$($entries:tt)*] $(#[$($attr:tt)*])* $vis:vis lazy static ref $ident:ident: $ty:ty = $expr:expr; $($tail:tt)*) => {
+		$crate::__magic_statics_parse! { $callback $args [$($entries)* { [$(#[$($attr)*])*] [$vis] $ident lazy [$ty] [$expr] }] $($tail)* }
+	};
 
-	($path:path) => {
-		$path.__init()
+	($callback:ident $args:tt $entries:tt $(#[$($attr:tt)*])* $vis:vis const ref $ident:ident: $ty:ty = #[cfg($($cfg:tt)*)] $primary:block else $fallback:block; $($tail:tt)*) => {
+		$crate::__magic_statics_parse! {
+			$callback $args $entries
+			#[cfg($($cfg)*)] $(#[$($attr)*])* $vis const ref $ident: $ty = $primary;
+			#[cfg(not($($cfg)*))] $(#[$($attr)*])* $vis const ref $ident: $ty = $fallback;
+			$($tail)*
+		}
 	};
 
-	($path:path, $($tail:tt)*) => {{
-		$path.__init();
-		$crate::init!($($tail)*);
-	}};
-}
+	($callback:ident $args:tt [$($entries:tt)*] $(#[$($attr:tt)*])* $vis:vis const ref $ident:ident: $ty:ty = $expr:expr; $($tail:tt)*) => {
+		$crate::__magic_statics_parse! { $callback $args [$($entries)* { [$(#[$($attr)*])*] [$vis] $ident const [$ty] [$expr] }] $($tail)* }
+	};
+
+	($callback:ident $args:tt $entries:tt $(#[$($attr:tt)*])* $vis:vis static async ref $ident:ident: $ty:ty = #[cfg($($cfg:tt)*)] $primary:block else $fallback:block; $($tail:tt)*) => {
+		$crate::__magic_statics_parse! {
+			$callback $args $entries
+			#[cfg($($cfg)*)] $(#[$($attr)*])* $vis static async ref $ident: $ty = $primary;
+			#[cfg(not($($cfg)*))] $(#[$($attr)*])* $vis static async ref $ident: $ty = $fallback;
+			$($tail)*
+		}
+	};
+
+	($callback:ident $args:tt [$($entries:tt)*] $(#[$($attr:tt)*])* $vis:vis static async ref $ident:ident: $ty:ty = $expr:expr; $($tail:tt)*) => {
+		$crate::__magic_statics_parse! { $callback $args [$($entries)* { [$(#[$($attr)*])*] [$vis] $ident async [$ty] [$expr] }] $($tail)* }
+	};
+
+	($callback:ident $args:tt $entries:tt $(#[$($attr:tt)*])* $vis:vis static try ref $ident:ident: Result<$ty:ty, $err:ty> = #[cfg($($cfg:tt)*)] $primary:block else $fallback:block; $($tail:tt)*) => {
+		$crate::__magic_statics_parse! {
+			$callback $args $entries
+			#[cfg($($cfg)*)] $(#[$($attr)*])* $vis static try ref $ident: Result<$ty, $err> = $primary;
+			#[cfg(not($($cfg)*))] $(#[$($attr)*])* $vis static try ref $ident: Result<$ty, $err> = $fallback;
+			$($tail)*
+		}
+	};
+
+	($callback:ident $args:tt [$($entries:tt)*] $(#[$($attr:tt)*])* $vis:vis static try ref $ident:ident: Result<$ty:ty, $err:ty> = $expr:expr; $($tail:tt)*) => {
+		$crate::__magic_statics_parse! { $callback $args [$($entries)* { [$(#[$($attr)*])*] [$vis] $ident try [$ty, $err] [$expr] }] $($tail)* }
+	};
+
+	($callback:ident $args:tt $entries:tt $(#[$($attr:tt)*])* $vis:vis static env ref $ident:ident: $ty:ty = $expr:expr; $($tail:tt)*) => {
+		$crate::__magic_statics_parse! { $callback $args $entries $(#[$($attr)*])* $vis static env(stringify!($ident)) ref $ident: $ty = $expr; $($tail)* }
+	};
+
+	($callback:ident $args:tt $entries:tt $(#[$($attr:tt)*])* $vis:vis static env($var:expr) ref $ident:ident: $ty:ty = $expr:expr; $($tail:tt)*) => {
+		$crate::__magic_statics_parse! { $callback $args $entries $(#[$($attr)*])* $vis static ref $ident: $ty = $crate::private::__from_env::<$ty>($var, || $expr); $($tail)* }
+	};
+
+	($callback:ident $args:tt $entries:tt $(#[$($attr:tt)*])* $vis:vis static $modifier:tt $ident:ident: $ty:ty = $expr:expr; $($tail:tt)*) => {
+		$crate::__magic_statics_expected_ref!([static $modifier] $ident $modifier);
+		$crate::__magic_statics_parse! { $callback $args $entries $($tail)* }
+	};
+
+	($callback:ident $args:tt $entries:tt $(#[$($attr:tt)*])* $vis:vis static $ident:tt: $ty:ty = $expr:expr; $($tail:tt)*) => {
+		$crate::__magic_statics_expected_ref!([static] $ident $ident);
+		$crate::__magic_statics_parse! { $callback $args $entries $($tail)* }
+	};
+
+	($callback:ident $args:tt $entries:tt $(#[$($attr:tt)*])* $vis:vis static $modifier:tt $ident:ident $($tail:tt)*) => {
+		$crate::__magic_statics_expected_ref!([static $modifier] $ident $modifier);
+	};
+
+	($callback:ident $args:tt $entries:tt $(#[$($attr:tt)*])* $vis:vis static $ident:tt: $($tail:tt)*) => {
+		$crate::__magic_statics_expected_ref!([static] $ident $ident);
+	};
+}
+
+#[macro_export]
+#[doc(hidden)]
+/// Reports a `static` declared without `ref` in `magic_statics!`. A `compile_error!` can only point at the whole invocation, so the token where `ref` was
+/// expected is also passed to an arm which can't match it, which makes rustc point at the exact declaration that's missing `ref`. The tokens must be
+/// captured as `tt`s along the way: `ident` fragments lose their span.
+macro_rules! __magic_statics_expected_ref {
+	([$($got:tt)*] $ident:tt $unexpected:tt) => {
+		compile_error!(concat!("Expected `static ref`, got `", stringify!($($got)*), "` for `", stringify!($ident), "`"));
+		$crate::__magic_statics_expected_ref!(static $unexpected);
+	};
+
+	(static ref) => {};
+}
+
+#[macro_export]
+#[doc(hidden)]
+macro_rules! __magic_statics_items {
+	($({ $($entry:tt)* })*) => {
+		$($crate::__magic_statics_item! { [] [] [] [] $($entry)* })*
+	};
+}
+
+#[macro_export]
+#[doc(hidden)]
+/// Generates the magic statics of a lazy group, whose `static ref`s are initialized with the whole group when any of them is first accessed.
+macro_rules! __magic_statics_lazy_items {
+	($({ $($entry:tt)* })*) => {
+		$($crate::__magic_statics_lazy_item! { $($entry)* })*
+	};
+}
+
+#[macro_export]
+#[doc(hidden)]
+macro_rules! __magic_statics_lazy_item {
+	($attrs:tt $vis:tt $ident:ident ref $($item:tt)*) => {
+		$crate::__magic_statics_item! { [] [] [] [] $attrs $vis $ident grouped $($item)* }
+	};
+
+	($attrs:tt $vis:tt $ident:ident async $($item:tt)*) => {
+		compile_error!(concat!("`", stringify!($ident), "` can't be in a lazy group: async magic statics can't be initialized lazily"));
+	};
+
+	($attrs:tt $vis:tt $ident:ident try $($item:tt)*) => {
+		compile_error!(concat!("`", stringify!($ident), "` can't be in a lazy group: fallible magic statics can't be initialized lazily"));
+	};
+
+	($($entry:tt)*) => {
+		$crate::__magic_statics_item! { [] [] [] [] $($entry)* }
+	};
+}
+
+#[macro_export]
+#[doc(hidden)]
+/// Generates a single magic static, extracting its `#[depends_on(...)]`, `#[group = "..."]` and `#[check = ...]` attributes.
+macro_rules! __magic_statics_item {
+	([$($kept:tt)*] [$($deps:tt)*] $group:tt $check:tt [#[depends_on($($dep:path),* $(,)?)] $($attrs:tt)*] $($item:tt)*) => {
+		$crate::__magic_statics_item! { [$($kept)*] [$($deps)* $($dep,)*] $group $check [$($attrs)*] $($item)* }
+	};
+
+	($kept:tt $deps:tt [] $check:tt [#[group = $group:literal] $($attrs:tt)*] $($item:tt)*) => {
+		$crate::__magic_statics_item! { $kept $deps [group = $group,] $check [$($attrs)*] $($item)* }
+	};
+
+	($kept:tt $deps:tt $group:tt [] [#[check = always] $($attrs:tt)*] $($item:tt)*) => {
+		$crate::__magic_statics_item! { $kept $deps $group [true] [$($attrs)*] $($item)* }
+	};
+
+	($kept:tt $deps:tt $group:tt [] [#[check = never] $($attrs:tt)*] $($item:tt)*) => {
+		$crate::__magic_statics_item! { $kept $deps $group [false] [$($attrs)*] $($item)* }
+	};
+
+	($kept:tt $deps:tt $group:tt $check:tt [#[check = $($invalid:tt)*] $($attrs:tt)*] $($item:tt)*) => {
+		compile_error!("Expected a single `#[check = always]` or `#[check = never]`");
+	};
+
+	([$($kept:tt)*] $deps:tt $group:tt $check:tt [#[$($attr:tt)*] $($attrs:tt)*] $($item:tt)*) => {
+		$crate::__magic_statics_item! { [$($kept)* #[$($attr)*]] $deps $group $check [$($attrs)*] $($item)* }
+	};
+
+	([$($kept:tt)*] [$($dep:path,)*] $group:tt [$check:literal] [] [$vis:vis] $ident:ident ref [$ty:ty] [$expr:expr]) => {
+		$($kept)*
+		$vis static $ident: $crate::CheckedMagicStatic<$ty, $check> = $crate::CheckedMagicStatic::__new($crate::MagicStatic::<$ty>::named(stringify!($ident), || $expr).__depends_on(&[$(&$dep),*]).__require_sync());
+
+		$crate::__magic_statics_entry!(__magic_static_register $group $ident [$($kept)*]);
+	};
+
+	([$($kept:tt)*] [$($dep:path,)*] $group:tt [$check:literal] [] [$vis:vis] $ident:ident async [$ty:ty] [$expr:expr]) => {
+		$($kept)*
+		$vis static $ident: $crate::CheckedMagicStatic<$ty, $check> = $crate::CheckedMagicStatic::__new($crate::MagicStatic::<$ty>::named_async(stringify!($ident), || $crate::private::Box::pin(async move { $expr })).__depends_on(&[$(&$dep),*]).__require_sync());
+
+		$crate::__magic_statics_entry!(__magic_static_register $group $ident [$($kept)*]);
+	};
+
+	([$($kept:tt)*] [$($dep:path,)*] $group:tt [$check:literal] [] [$vis:vis] $ident:ident try [$ty:ty, $err:ty] [$expr:expr]) => {
+		$($kept)*
+		$vis static $ident: $crate::CheckedMagicStatic<$ty, $check, $err> = $crate::CheckedMagicStatic::__new($crate::MagicStatic::<$ty, $err>::try_named(stringify!($ident), || ::core::result::Result::Ok($expr)).__depends_on(&[$(&$dep),*]).__require_sync());
+
+		$crate::__magic_statics_entry!(__magic_static_register $group $ident [$($kept)*]);
+	};
+
+	($kept:tt $deps:tt $group:tt [$check:literal] [] $vis:tt $ident:ident $kind:tt $($item:tt)*) => {
+		compile_error!("`#[check = ...]` can't be used on `lazy static ref` or `const ref` magic statics, which always and never check respectively");
+	};
+
+	([$($kept:tt)*] [$($dep:path,)*] $group:tt [] [] [$vis:vis] $ident:ident ref [$ty:ty] [$expr:expr]) => {
+		$($kept)*
+		$vis static $ident: $crate::MagicStatic<$ty> = $crate::MagicStatic::<$ty>::named(stringify!($ident), || $expr).__depends_on(&[$(&$dep),*]).__require_sync();
+
+		$crate::__magic_statics_entry!(__magic_static_register $group $ident [$($kept)*]);
+	};
+
+	([$($kept:tt)*] [$($dep:path,)*] $group:tt [] [] [$vis:vis] $ident:ident grouped [$ty:ty] [$expr:expr]) => {
+		$($kept)*
+		$vis static $ident: $crate::LazyMagicStatic<$ty> = {
+			// Bound to a constant first so that the initializer isn't in the `unsafe` block
+			#[allow(clippy::declare_interior_mutable_const)]
+			const __MAGIC_STATIC: $crate::MagicStatic<$ty> = $crate::MagicStatic::<$ty>::named(stringify!($ident), || $expr).__depends_on(&[$(&$dep),*]).__require_sync();
+			unsafe { $crate::LazyMagicStatic::__new_in_group(__MAGIC_STATIC, &__MAGIC_STATICS_LAZY_GROUP) }
+		};
+
+		$crate::__magic_statics_entry!(__magic_static_register $group $ident [$($kept)*]);
+	};
+
+	([$($kept:tt)*] [$($dep:path,)*] $group:tt [] [] [$vis:vis] $ident:ident lazy [$ty:ty] [$expr:expr]) => {
+		$($kept)*
+		$vis static $ident: $crate::LazyMagicStatic<$ty> = {
+			// Bound to a constant first so that the initializer isn't in the `unsafe` block
+			#[allow(clippy::declare_interior_mutable_const)]
+			const __MAGIC_STATIC: $crate::MagicStatic<$ty> = $crate::MagicStatic::<$ty>::named(stringify!($ident), || $expr).__depends_on(&[$(&$dep),*]).__require_sync();
+			unsafe { $crate::LazyMagicStatic::__new(__MAGIC_STATIC) }
+		};
+
+		$crate::__magic_statics_entry!(__magic_static_register $group $ident [$($kept)*]);
+	};
+
+	([$($kept:tt)*] [$($dep:path,)*] [] [] [] [$vis:vis] $ident:ident const [$ty:ty] [$expr:expr]) => {
+		$($kept)*
+		$vis static $ident: $crate::ConstMagicStatic<$ty> = $crate::ConstMagicStatic::new($expr);
+	};
+
+	([$($kept:tt)*] [$($dep:path,)*] $group:tt [] [] [$vis:vis] $ident:ident async [$ty:ty] [$expr:expr]) => {
+		$($kept)*
+		$vis static $ident: $crate::MagicStatic<$ty> = $crate::MagicStatic::<$ty>::named_async(stringify!($ident), || $crate::private::Box::pin(async move { $expr })).__depends_on(&[$(&$dep),*]).__require_sync();
+
+		$crate::__magic_statics_entry!(__magic_static_register $group $ident [$($kept)*]);
+	};
+
+	([$($kept:tt)*] [$($dep:path,)*] $group:tt [] [] [$vis:vis] $ident:ident try [$ty:ty, $err:ty] [$expr:expr]) => {
+		$($kept)*
+		$vis static $ident: $crate::MagicStatic<$ty, $err> = $crate::MagicStatic::<$ty, $err>::try_named(stringify!($ident), || ::core::result::Result::Ok($expr)).__depends_on(&[$(&$dep),*]).__require_sync();
+
+		$crate::__magic_statics_entry!(__magic_static_register $group $ident [$($kept)*]);
+	};
+}
+
+#[macro_export]
+/// The same as `magic_statics!` but automatically generates the module-level `magic_static` function for you:
+///
+/// **You can only have one of these per module (scope)** - if you want to initialize magic statics in a group, define a `magic_static` function in your module yourself! (See the example)
+///
+/// Alternatively, you can give the group a name with `init_group = name { ... }` to have as many groups as you like in one module. Named groups are initialized with `mod path::to::module::name`.
+///
+/// The magic statics are initialized **in declaration order**, so a magic static can access the magic statics declared before it in the group during its
+/// initialization.
+///
+/// A `magic_static_initialized` function is also generated, which returns whether every magic static in the group has been initialized.
+///
+/// The group can declare child groups (modules or named groups, relative to the module) with `children = [...];` before its magic statics. Initializing
+/// the group with `mod path::to::module::*` then also initializes its children, recursively.
+///
+/// The generated functions are `pub` by default. To keep them out of your public API, give the group a visibility, like
+/// `magic_statics_mod!(pub(crate) { ... })` or `magic_statics_mod!(pub(super) init_group = name { ... })`.
+///
+/// A library which can't rely on its users to initialize its magic statics can declare a lazy group with `magic_statics_mod!(lazy { ... })`: the first
+/// access to any of its `static ref`s initializes the whole group, in declaration order. They are [`LazyMagicStatic`]s, so **every access pays for a
+/// check**, and the group can still be initialized eagerly with `mod path::to::module`.
+///
+/// # Example
+///
+/// ```rust
+/// # #[macro_use] extern crate magic_static;
+/// mod foo {
+///     // Note the use of `magic_statics_mod!` rather than `magic_statics!` here
+///     magic_statics_mod! {
+///         pub(super) static ref MAGIC: usize = {
+///             println!("Magic!");
+///             42
+///         };
+///
+///         pub(super) static ref BAR: std::sync::Mutex<()> = std::sync::Mutex::new(());
+///     }
+///
+///     // Will generate the following:
+///     /*
+///     #[magic_static::main(
+///         MAGIC,
+///         BAR
+///     )]
+///     pub fn magic_static() {}
+///     */
+///
+///     // Named groups can live alongside each other in the same module
+///     magic_statics_mod!(init_group = startup_group {
+///         pub(super) static ref STARTUP: usize = 1;
+///     });
+/// }
+///
+/// #[magic_static::main(
+///     mod foo, // This will initialize all magic statics in `foo`
+///     mod foo::startup_group // This will initialize all magic statics in the `startup_group` group of `foo`
+/// )]
+/// fn main() {
+///     assert!(foo::magic_static_initialized());
+///     assert!(foo::startup_group::magic_static_initialized());
+/// }
+/// ```
+macro_rules! magic_statics_mod {
+	{ lazy { $($tt:tt)* } } => {
+		$crate::__magic_statics_parse! { __magic_statics_lazy_items [] [] $($tt)* }
+		$crate::__magic_statics_parse! { __magic_statics_mod_fns [[pub] [] []] [] $($tt)* }
+
+		#[doc(hidden)]
+		static __MAGIC_STATICS_LAZY_GROUP: $crate::MagicStatic<()> = $crate::MagicStatic::named(concat!(module_path!(), "::<lazy group>"), || {
+			magic_static();
+		});
+	};
+
+	{ init_group = $group:ident $body:tt } => {
+		$crate::magic_statics_mod! { pub init_group = $group $body }
+	};
+
+	{ $fn_vis:vis init_group = $group:ident { children = [$($($child:ident)::+),* $(,)?]; $($tt:tt)* } } => {
+		$crate::__magic_statics_mod! { [$fn_vis] [$group] [$([$($child)::+])*] $($tt)* }
+	};
+
+	{ $fn_vis:vis init_group = $group:ident { $($tt:tt)* } } => {
+		$crate::__magic_statics_mod! { [$fn_vis] [$group] [] $($tt)* }
+	};
+
+	{ pub $(($($restriction:tt)*))? { children = [$($($child:ident)::+),* $(,)?]; $($tt:tt)* } } => {
+		$crate::__magic_statics_mod! { [pub $(($($restriction)*))?] [] [$([$($child)::+])*] $($tt)* }
+	};
+
+	{ pub $(($($restriction:tt)*))? { $($tt:tt)* } } => {
+		$crate::__magic_statics_mod! { [pub $(($($restriction)*))?] [] [] $($tt)* }
+	};
+
+	{ children = [$($($child:ident)::+),* $(,)?]; $($tt:tt)* } => {
+		$crate::__magic_statics_mod! { [pub] [] [$([$($child)::+])*] $($tt)* }
+	};
+
+	{ $($tt:tt)* } => {
+		$crate::__magic_statics_mod! { [pub] [] [] $($tt)* }
+	};
+}
+
+#[macro_export]
+#[doc(hidden)]
+macro_rules! __magic_statics_mod {
+	{ [$fn_vis:vis] [$group:ident] $children:tt $($(#[$($attr:tt)*])* $vis:vis static ref $ident:ident: $ty:ty = $expr:expr;)* } => {
+		$crate::magic_statics!($($(#[$($attr)*])* $vis static ref $ident: $ty = $expr;)*);
+
+		#[doc(hidden)]
+		$fn_vis mod $group {
+			$crate::__magic_statics_mod_fns! { [pub] [super::] $children $({ [$(#[$($attr)*])*] [$vis] $ident ref [$ty] [$expr] })* }
+		}
+	};
+
+	{ [$fn_vis:vis] [$group:ident] $children:tt $($tt:tt)* } => {
+		$crate::magic_statics! { $($tt)* }
+
+		#[doc(hidden)]
+		$fn_vis mod $group {
+			$crate::__magic_statics_parse! { __magic_statics_mod_fns [[pub] [super::] $children] [] $($tt)* }
+		}
+	};
+
+	{ [$fn_vis:vis] [] $children:tt $($(#[$($attr:tt)*])* $vis:vis static ref $ident:ident: $ty:ty = $expr:expr;)* } => {
+		$crate::magic_statics!($($(#[$($attr)*])* $vis static ref $ident: $ty = $expr;)*);
+		$crate::__magic_statics_mod_fns! { [$fn_vis] [] $children $({ [$(#[$($attr)*])*] [$vis] $ident ref [$ty] [$expr] })* }
+	};
+
+	{ [$fn_vis:vis] [] $children:tt $($tt:tt)* } => {
+		$crate::magic_statics! { $($tt)* }
+		$crate::__magic_statics_parse! { __magic_statics_mod_fns [[$fn_vis] [] $children] [] $($tt)* }
+	};
+}
+
+#[macro_export]
+#[doc(hidden)]
+macro_rules! __magic_statics_mod_fns {
+	([$fn_vis:vis] $prefix:tt $children:tt $({ [$($attrs:tt)*] $vis:tt $ident:ident $($entry:tt)* })*) => {
+		#[doc(hidden)]
+		#[inline]
+		#[allow(dead_code, deprecated)]
+		$fn_vis fn magic_static() -> usize {
+			let mut count = 0;
+			$($crate::__magic_statics_entry!(__magic_statics_init_counted [count $prefix] $ident [$($attrs)*]);)*
+			count
+		}
+
+		#[doc(hidden)]
+		#[inline]
+		#[allow(dead_code, deprecated)]
+		$fn_vis fn magic_static_recursive() -> usize {
+			let mut count = magic_static();
+			$crate::__magic_statics_init_children!(count $prefix $children);
+			count
+		}
+
+		#[doc(hidden)]
+		#[inline]
+		#[allow(dead_code, deprecated)]
+		$fn_vis fn magic_static_initialized() -> bool {
+			let mut initialized = true;
+			$($crate::__magic_statics_entry!(__magic_statics_check_initialized [initialized $prefix] $ident [$($attrs)*]);)*
+			initialized
+		}
+
+		#[doc(hidden)]
+		#[inline]
+		#[allow(dead_code, deprecated)]
+		$fn_vis async fn magic_static_async() -> usize {
+			let mut count = 0;
+			$($crate::__magic_statics_entry!(__magic_statics_init_counted_async [count $prefix] $ident [$($attrs)*]);)*
+			count
+		}
+
+		#[doc(hidden)]
+		#[inline]
+		#[allow(dead_code, deprecated)]
+		$fn_vis async fn magic_static_recursive_async() -> usize {
+			let mut count = magic_static_async().await;
+			$crate::__magic_statics_init_children!(async count $prefix $children);
+			count
+		}
+
+		#[doc(hidden)]
+		#[inline]
+		#[allow(dead_code, deprecated)]
+		$fn_vis unsafe fn magic_static_deinit() {
+			$crate::__magic_statics_deinit_rev!([$(($prefix $ident [$($attrs)*]))*] []);
+		}
+	};
+}
+
+#[macro_export]
+#[doc(hidden)]
+/// Recursively initializes the child modules declared with `children = [...];` in `magic_statics_mod!`.
+macro_rules! __magic_statics_init_children {
+	($count:ident $prefix:tt []) => {};
+
+	(async $count:ident $prefix:tt []) => {};
+
+	($count:ident [$($prefix:tt)*] [[$($child:tt)*] $($tail:tt)*]) => {
+		$count += $crate::private::InitCount::__count($($prefix)* $($child)*::magic_static_recursive());
+		$crate::__magic_statics_init_children!($count [$($prefix)*] [$($tail)*]);
+	};
+
+	(async $count:ident [$($prefix:tt)*] [[$($child:tt)*] $($tail:tt)*]) => {
+		$count += $crate::private::InitCount::__count($($prefix)* $($child)*::magic_static_recursive_async().await);
+		$crate::__magic_statics_init_children!(async $count [$($prefix)*] [$($tail)*]);
+	};
+}
+
+#[macro_export]
+#[doc(hidden)]
+macro_rules! __magic_statics_init_counted {
+	($count:ident [$($prefix:tt)*] $ident:ident) => {
+		$count += $crate::init!($($prefix)* $ident);
+	};
+}
+
+#[macro_export]
+#[doc(hidden)]
+macro_rules! __magic_statics_check_initialized {
+	($initialized:ident [$($prefix:tt)*] $ident:ident) => {
+		$initialized &= $($prefix)* $ident.is_initialized();
+	};
+}
+
+#[macro_export]
+#[doc(hidden)]
+macro_rules! __magic_statics_init_counted_async {
+	($count:ident [$($prefix:tt)*] $ident:ident) => {
+		$count += $($prefix)* $ident.__init_async().await as usize;
+	};
+}
+
+#[macro_export]
+#[doc(hidden)]
+macro_rules! __magic_statics_deinit_rev {
+	([] [$(($prefix:tt $ident:ident $attrs:tt))*]) => {
+		$($crate::__magic_statics_entry!(deinit $prefix $ident $attrs);)*
+	};
+
+	([$head:tt $($tail:tt)*] [$($rev:tt)*]) => {
+		$crate::__magic_statics_deinit_rev!([$($tail)*] [$head $($rev)*])
+	};
+}
+
+#[macro_export]
+#[doc(hidden)]
+/// Invokes `init!`, `deinit!` or `__magic_static_register!` on a magic static, keeping only the `#[cfg]` attributes of its declaration.
+macro_rules! __magic_statics_entry {
+	($macro:ident [$($prefix:tt)*] $ident:ident [] $(#[$cfg:meta])*) => {
+		$(#[$cfg])*
+		$crate::$macro!($($prefix)* $ident);
+	};
+
+	($macro:ident $prefix:tt $ident:ident [#[cfg $cfg:tt] $($attrs:tt)*] $($cfgs:tt)*) => {
+		$crate::__magic_statics_entry! { $macro $prefix $ident [$($attrs)*] $($cfgs)* #[cfg $cfg] }
+	};
+
+	($macro:ident $prefix:tt $ident:ident [#[$($attr:tt)*] $($attrs:tt)*] $($cfgs:tt)*) => {
+		$crate::__magic_statics_entry! { $macro $prefix $ident [$($attrs)*] $($cfgs)* }
+	};
+}
+
+#[macro_export]
+/// Manually initializes the provided magic statics **in the specified order**.
+///
+/// Does nothing to a magic static if it has already been initialized.
+///
+/// `mod path::to::module::*` also initializes the children declared by the module's `magic_statics_mod!`, recursively. Module paths can start with `self`,
+/// `super` or `crate` (e.g. `mod super::config`, or just `mod self`), as in `#[magic_static::main]`.
+///
+/// Evaluates to the number of magic statics that were initialized by this invocation, as a `usize`, so it must be followed by a semicolon when used as a statement. Modules count the magic statics initialized by their `magic_static` function when it is generated by `magic_statics_mod!`, and zero otherwise.
+///
+/// # Safety
+///
+/// The following behaviour is considered undefined:
+///
+/// * Initializing magic statics from multiple threads concurrently, except with `magic_static::init_parallel!` or by accessing lazy magic statics.
+/// * Spawning new threads and accessing magic statics during initialization from them.
+/// * Interior mutability of magic statics where the mutability is not synchronized across multiple threads (e.g. with a Mutex or RwLock.) This is not a problem for single-threaded applications.
+///
+/// # Example
+///
+/// ```rust
+/// # #[macro_use] extern crate r#magic_static;
+/// mod foo {
+///     magic_statics! {
+///         pub(super) static ref BAR: std::sync::Mutex<()> = std::sync::Mutex::new(());
+///         pub(super) static ref MAGIC: usize = {
+///             println!("Magic!");
+///             42
+///         };
+///     }
+/// }
+///
+/// // You can also modularize your magic statics like so:
+/// mod baz {
+///     magic_statics! {
+///         pub(super) static ref MAGIC: usize = {
+///             println!("Magic!");
+///             42
+///         };
+///
+///         pub(super) static ref BAR: std::sync::Mutex<()> = std::sync::Mutex::new(());
+///     }
+///
+///     #[magic_static::main(
+///         MAGIC,
+///         BAR
+///     )]
+///     // Must be called `magic_static`
+///     pub fn magic_static() {}
+/// }
+///
+/// fn main() {
+///     let initialized = magic_static::init! {
+///         foo::BAR,
+///         foo::MAGIC,
+///         mod baz // This will initialize all magic statics in `baz`
+///     };
+///     println!("Initialized {} magic statics", initialized);
+///
+///     // These have already been initialized
+///     assert_eq!(magic_static::init! { foo::BAR, foo::MAGIC }, 0);
+/// }
+/// ```
+macro_rules! init {
+	() => {
+		0usize
+	};
+
+	(mod $($path:ident)::+ ::* $(,)?) => {
+		$crate::private::InitCount::__count($($path)::+::magic_static_recursive())
+	};
+
+	(mod $($path:ident)::+ ::*, $($tail:tt)*) => {{
+		let mut count = $crate::init!(mod $($path)::+ ::*);
+		count += $crate::init!($($tail)*);
+		count
+	}};
+
+	(mod $($path:ident)::+ $(,)?) => {
+		$crate::private::InitCount::__count($($path)::+::magic_static())
+	};
+
+	(mod $($path:ident)::+, $($tail:tt)*) => {{
+		let mut count = $crate::init!(mod $($path)::+);
+		count += $crate::init!($($tail)*);
+		count
+	}};
+
+	($path:path $(,)?) => {
+		$path.__init() as usize
+	};
+
+	($path:path, $($tail:tt)*) => {{
+		let mut count = $crate::init!($path);
+		count += $crate::init!($($tail)*);
+		count
+	}};
+}
+
+#[macro_export]
+#[cfg(feature = "parallel-init")]
+/// Initializes the given magic statics (and modules) **in parallel**, each on its own thread, returning once all of them have been initialized.
+///
+/// This is for speeding up startup with slow initializers. It takes the same arguments and returns the same count as `magic_static::init!`, and once it
+/// returns, the magic statics can be accessed from any thread. Requires the `parallel-init` feature flag.
+///
+/// The dependencies of a magic static (`depends_on`) are still initialized before it, on its thread. A dependency shared by several of the magic statics
+/// is initialized once, while the other threads wait for it. As the threads wait for each other, a dependency cycle across threads deadlocks instead of
+/// panicking (unless the `spin-timeout` feature flag is enabled.)
+///
+/// If an initializer panics, its magic static is poisoned, and the panic is propagated once every thread has finished.
+///
+/// # Example
+///
+/// ```rust
+/// # #[macro_use] extern crate magic_static;
+/// magic_statics! {
+///     static ref WORDS: Vec<String> = vec!["magic".to_string(), "static".to_string()];
+///
+///     #[depends_on(WORDS)]
+///     static ref LONGEST: usize = WORDS.iter().map(String::len).max().unwrap_or(0);
+///
+///     #[depends_on(WORDS)]
+///     static ref TOTAL: usize = WORDS.iter().map(String::len).sum();
+/// }
+///
+/// fn main() {
+///     // `WORDS` is initialized by whichever thread gets to it first
+///     assert_eq!(magic_static::init_parallel! { LONGEST, TOTAL }, 2);
+///     assert!(WORDS.is_initialized());
+///
+///     std::thread::spawn(|| assert_eq!((*LONGEST, *TOTAL), (6, 11))).join().unwrap();
+/// }
+/// ```
+macro_rules! init_parallel {
+	($($tt:tt)*) => {
+		$crate::__init_parallel!([] $($tt)*)
+	};
+}
+
+#[macro_export]
+#[doc(hidden)]
+#[cfg(feature = "parallel-init")]
+/// Wraps each argument of `init_parallel!` in its own call to `init!`.
+macro_rules! __init_parallel {
+	([$($init:expr,)*]) => {
+		$crate::private::__init_parallel(&[$(|| $init),*])
+	};
+
+	([$($init:tt)*] mod $($path:ident)::+ ::* $(, $($tail:tt)*)?) => {
+		$crate::__init_parallel!([$($init)* $crate::init!(mod $($path)::+ ::*),] $($($tail)*)?)
+	};
+
+	([$($init:tt)*] mod $($path:ident)::+ $(, $($tail:tt)*)?) => {
+		$crate::__init_parallel!([$($init)* $crate::init!(mod $($path)::+),] $($($tail)*)?)
+	};
+
+	([$($init:tt)*] $path:path $(, $($tail:tt)*)?) => {
+		$crate::__init_parallel!([$($init)* $crate::init!($path),] $($($tail)*)?)
+	};
+}
+
+#[macro_export]
+/// Marks where `#[magic_static::main]` initializes its magic statics in the body of the function it is attached to, instead of at the start.
+///
+/// Must be used as a statement directly in the body of the function.
+///
+/// # Example
+///
+/// ```rust
+/// # #[macro_use] extern crate magic_static;
+/// magic_statics! {
+///     static ref MAGIC: usize = {
+///         println!("Initializing MAGIC");
+///         42
+///     };
+/// }
+///
+/// #[magic_static::main(MAGIC)]
+/// fn main() {
+///     println!("Installing a panic hook before initializing MAGIC");
+///     std::panic::set_hook(Box::new(|info| eprintln!("Oh no! {}", info)));
+///
+///     magic_static::init_here!();
+///
+///     assert_eq!(*MAGIC, 42);
+/// }
+/// ```
+macro_rules! init_here {
+	() => {
+		compile_error!("`magic_static::init_here!()` can only be used directly in the body of a function annotated with `#[magic_static::main]`");
+	};
+}
+
+#[macro_export]
+/// Creates a [`MagicStatic`] for an array which is initialized element by element, by calling the given closure with the index of each element.
+///
+/// The elements are written directly into the magic static, so even a large array never passes through the stack during initialization, unlike
+/// an initializer which returns the whole array. The closure can't capture variables, but can access other magic statics.
+///
+/// # Example
+///
+/// ```rust
+/// use magic_static::MagicStatic;
+///
+/// static SQUARES: MagicStatic<[u64; 4096]> = magic_static::array_from_fn!(|i| (i * i) as u64);
+///
+/// fn main() {
+///     magic_static::init! { SQUARES };
+///     assert_eq!(SQUARES[12], 144);
+/// }
+/// ```
+///
+/// The closure isn't in an `unsafe` context, so calling an `unsafe` function from it needs an `unsafe` block:
+///
+/// ```compile_fail,E0133
+/// unsafe fn square(i: usize) -> u64 {
+///     (i * i) as u64
+/// }
+///
+/// static SQUARES: magic_static::MagicStatic<[u64; 4096]> = magic_static::array_from_fn!(|i| square(i));
+/// # fn main() {}
+/// ```
+macro_rules! array_from_fn {
+	($f:expr $(,)?) => {{
+		// Bound first so that the closure isn't in the `unsafe` block
+		let init = $crate::private::__in_place_init(|array| $crate::private::__fill_array(array, $f));
+		unsafe { $crate::MagicStatic::new_in_place(init) }
+	}};
+}
+
+#[macro_export]
+/// Manually initializes the provided magic statics **in the specified order**, returning early with the error of the first fallible initializer that fails.
+///
+/// This must be used in a function returning a `Result` whose error type can be converted from the errors of the magic statics' initializers (like the `?` operator.) Infallible magic statics should be initialized with `magic_static::init!` instead.
+///
+/// Does nothing to a magic static if it has already been initialized.
+///
+/// # Safety
+///
+/// See the safety section of `magic_static::init!`.
+///
+/// # Example
+///
+/// ```rust
+/// # #[macro_use] extern crate magic_static;
+/// magic_statics! {
+///     static try ref PORT: Result<u16, std::num::ParseIntError> = std::env::var("PORT").as_deref().unwrap_or("8080").parse()?;
+///     static ref HOST: &'static str = "localhost";
+/// }
+///
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     magic_static::init! { HOST };
+///     magic_static::init_try! { PORT }
+///
+///     println!("Listening on {}:{}", *HOST, *PORT);
+///     Ok(())
+/// }
+/// ```
+macro_rules! init_try {
+	() => {};
+
+	($path:path $(,)?) => {
+		$path.try_init_checked()?
+	};
+
+	($path:path, $($tail:tt)*) => {{
+		$path.try_init_checked()?;
+		$crate::init_try!($($tail)*);
+	}};
+}
+
+#[macro_export]
+/// Manually initializes the provided magic statics **in the specified order**, giving a shared context to those created with
+/// [`MagicStatic::with_context`], for initializers which need data only available at runtime (e.g. parsed command line arguments.)
+///
+/// The context is evaluated once, must be a reference, and is only borrowed while the magic statics are initialized. Magic statics which don't take
+/// a context are initialized as usual, so they can be listed too (but modules can't.)
+///
+/// Evaluates to the number of magic statics that were initialized by this invocation, like `magic_static::init!`.
+///
+/// # Safety
+///
+/// See the safety section of `magic_static::init!`.
+///
+/// # Example
+///
+/// ```rust
+/// use magic_static::MagicStatic;
+///
+/// struct Args {
+///     name: String,
+/// }
+///
+/// static GREETING: MagicStatic<String> = MagicStatic::with_context(|args: &Args| format!("Hello, {}!", args.name));
+/// static NAME_LEN: MagicStatic<usize> = MagicStatic::with_context(|args: &Args| args.name.len());
+///
+/// fn main() {
+///     let args = Args { name: std::env::args().nth(1).unwrap_or_else(|| "world".to_string()) };
+///     assert_eq!(magic_static::init_with! { &args => GREETING, NAME_LEN }, 2);
+///
+///     println!("{} ({} characters)", *GREETING, *NAME_LEN);
+/// }
+/// ```
+macro_rules! init_with {
+	($context:expr => $($path:path),* $(,)?) => {{
+		let context = $context;
+		let mut count = 0usize;
+		$(count += $path.init_with(context) as usize;)*
+		count
+	}};
+}
+
+#[macro_export]
+/// Manually deinitializes the provided magic statics **in the specified order**, running their destructors.
+///
+/// Magic statics are never dropped automatically (unless the `drop-at-exit` feature flag is enabled), so this can be used to release the resources they hold (e.g. at shutdown.)
+///
+/// Does nothing to a magic static if it isn't initialized. A deinitialized magic static can be initialized again with `init!`.
+///
+/// `mod` entries call the module's `magic_static_deinit` function, which is generated by `magic_statics_mod!` and deinitializes the module's magic statics in reverse order of declaration.
+///
+/// # Safety
+///
+/// This macro calls unsafe functions and must be used inside an `unsafe` block.
+///
+/// The following behaviour is considered undefined:
+///
+/// * Deinitializing a magic static while references to its value are still alive, or accessing it afterwards without initializing it again.
+/// * Deinitializing or initializing magic statics from multiple threads concurrently.
+///
+/// # Example
+///
+/// ```rust
+/// # #[macro_use] extern crate magic_static;
+/// mod foo {
+///     magic_statics! {
+///         pub(super) static ref BAR: std::sync::Mutex<()> = std::sync::Mutex::new(());
+///     }
+/// }
+///
+/// mod baz {
+///     magic_statics_mod! {
+///         pub(super) static ref MAGIC: Vec<usize> = vec![42];
+///     }
+/// }
+///
+/// fn main() {
+///     magic_static::init! {
+///         foo::BAR,
+///         mod baz
+///     };
+///
+///     unsafe {
+///         magic_static::deinit! {
+///             mod baz,
+///             foo::BAR
+///         }
+///     }
+/// }
+/// ```
+macro_rules! deinit {
+	() => {};
+
+	(mod $($path:ident)::+ $(,)?) => {
+		$($path)::+::magic_static_deinit()
+	};
+
+	(mod $($path:ident)::+, $($tail:tt)*) => {{
+		$($path)::+::magic_static_deinit();
+		$crate::deinit!($($tail)*);
+	}};
+
+	($path:path $(,)?) => {
+		$path.__deinit()
+	};
+
+	($path:path, $($tail:tt)*) => {{
+		$path.__deinit();
+		$crate::deinit!($($tail)*);
+	}};
+}
+
+#[macro_export]
+/// Deinitializes every initialized magic static in the program, **in the reverse order they were initialized**, dropping their values.
+///
+/// A magic static is initialized after the magic statics it accesses during its initialization, so it is dropped before them. Thread-local magic statics aren't deinitialized.
+///
+/// Requires the `std` or `alloc` feature flag.
+///
+/// # Safety
+///
+/// This macro calls unsafe functions and must be used inside an `unsafe` block.
+///
+/// See the safety section of `magic_static::deinit!`.
+///
+/// # Example
+///
+/// ```rust
+/// # #[macro_use] extern crate magic_static;
+/// magic_statics! {
+///     #[depends_on(CONNECTION)]
+///     static ref SESSION: String = format!("session on {}", *CONNECTION);
+///     static ref CONNECTION: String = String::from("localhost");
+/// }
+///
+/// #[magic_static::main(SESSION)]
+/// fn main() {
+///     // `SESSION` is dropped before `CONNECTION`
+///     unsafe { magic_static::deinit_all!() };
+///     assert!(!CONNECTION.is_initialized());
+/// }
+/// ```
+#[cfg(any(feature = "std", feature = "alloc"))]
+macro_rules! deinit_all {
+	() => {
+		$crate::private::__deinit_all()
+	};
+}
+
+#[macro_export]
+/// Initializes every magic static in the program, **in an unspecified order**, apart from dependencies (`depends_on`) and the ordering constraints
+/// declared with `#[magic_static(before(...))]` and `#[magic_static(after(...))]`, which are honoured. A cycle among them panics, naming the cycle.
+///
+/// Requires the `auto-register` feature flag.
+///
+/// Does nothing to a magic static if it has already been initialized.
+///
+/// # Safety
+///
+/// The following behaviour is considered undefined:
+///
+/// * Initializing magic statics from multiple threads concurrently, except with `magic_static::init_parallel!` or by accessing lazy magic statics.
+/// * Spawning new threads and accessing magic statics during initialization from them.
+/// * Interior mutability of magic statics where the mutability is not synchronized across multiple threads (e.g. with a Mutex or RwLock.) This is not a problem for single-threaded applications.
+///
+/// # Example
+///
+/// ```rust
+/// # #[macro_use] extern crate magic_static;
+/// mod foo {
+///     magic_statics! {
+///         pub(super) static ref MAGIC: usize = 42;
+///     }
+/// }
+///
+/// #[magic_static]
+/// static BAR: usize = 69;
+///
+/// fn main() {
+///     magic_static::init_all!();
+///
+///     assert_eq!(*foo::MAGIC, 42);
+///     assert_eq!(*BAR, 69);
+/// }
+/// ```
+///
+/// Ordering constraints let each magic static declare its place in the order locally:
+///
+/// ```rust
+/// # #[macro_use] extern crate magic_static;
+/// static ORDER: std::sync::Mutex<Vec<&str>> = std::sync::Mutex::new(Vec::new());
+///
+/// #[magic_static(after(CONFIG))]
+/// static DATABASE: &str = { ORDER.lock().unwrap().push("database"); "database" };
+///
+/// #[magic_static(before(DATABASE))]
+/// static LOGGER: &str = { ORDER.lock().unwrap().push("logger"); "logger" };
+///
+/// #[magic_static(before(LOGGER))]
+/// static CONFIG: &str = { ORDER.lock().unwrap().push("config"); "config" };
+///
+/// fn main() {
+///     # if CONFIG.is_initialized() { return; } // Already initialized before `main` by the `startup` feature flag, which doesn't honour ordering constraints
+///     magic_static::init_all!();
+///     assert_eq!(*ORDER.lock().unwrap(), ["config", "logger", "database"]);
+/// }
+/// ```
+#[cfg(feature = "auto-register")]
+macro_rules! init_all {
+	() => {
+		$crate::private::__init_all()
+	};
+}
+
+#[macro_export]
+/// Initializes every magic static in the program which belongs to the given group, **in an unspecified order**.
+///
+/// A magic static is added to a group with `#[group = "name"]` in `magic_statics!`, or `#[magic_static(group = "name")]`. This allows initializing the magic statics of
+/// a subsystem together, regardless of which modules they are declared in.
+///
+/// Requires the `auto-register` feature flag.
+///
+/// Does nothing to a magic static if it has already been initialized.
+///
+/// # Safety
+///
+/// See the safety section of `magic_static::init_all!`.
+///
+/// # Example
+///
+/// ```rust
+/// # #[macro_use] extern crate magic_static;
+/// mod http {
+///     magic_statics! {
+///         #[group = "net"]
+///         pub(super) static ref PORT: u16 = 8080;
+///     }
+/// }
+///
+/// mod dns {
+///     #[magic_static(group = "net")]
+///     pub(super) static RESOLVER: &str = "1.1.1.1";
+/// }
+///
+/// fn main() {
+///     magic_static::init_group!("net");
+///
+///     assert_eq!(*http::PORT, 8080);
+///     assert_eq!(*dns::RESOLVER, "1.1.1.1");
+/// }
+/// ```
+#[cfg(feature = "auto-register")]
+macro_rules! init_group {
+	($group:expr) => {
+		$crate::private::__init_group($group)
+	};
+}
\ No newline at end of file
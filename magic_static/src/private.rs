@@ -1,118 +1,3092 @@
 use core::{cell::UnsafeCell, mem::MaybeUninit};
 
-#[macro_export]
+/// A global singleton that is initialized manually at program start.
+///
+/// Magic statics are usually declared with the `magic_statics!` macro or the `#[magic_static]` attribute, but can also be constructed with [`MagicStatic::new`].
+///
+/// # Poisoning
+///
+/// If the initializer of a magic static panics (or, for async magic statics, its future is dropped before completing), the magic static becomes *poisoned*: it will never be initialized, and any
+/// further attempt to initialize it (including threads that were already waiting for it to be initialized) will panic
+/// rather than wait forever.
+pub struct MagicStatic<T, E = core::convert::Infallible> {
+	#[cfg(not(feature = "bare-metal"))]
+	initialized: core::sync::atomic::AtomicU8,
+
+	/// Has the same states as the atomic, so that the value is only considered initialized once it has been written
+	#[cfg(feature = "bare-metal")]
+	initialized: UnsafeCell<u8>,
+
+	value: UnsafeCell<MaybeUninit<T>>,
+
+	init: Init<T, E>,
+
+	name: &'static str,
+
+	depends_on: &'static [&'static dyn AnyMagicStatic],
+
+	/// Set once `take_mut_once` has handed out a mutable reference
+	#[cfg(all(feature = "unsafe-mut", not(feature = "bare-metal")))]
+	taken_mut: core::sync::atomic::AtomicBool,
+
+	#[cfg(all(feature = "unsafe-mut", feature = "bare-metal"))]
+	taken_mut: UnsafeCell<bool>,
+}
+
+enum Init<T, E> {
+	Infallible(fn() -> T),
+	Fallible(fn() -> Result<T, E>),
+
+	/// Writes the value directly into the magic static
+	InPlace(fn(&mut MaybeUninit<T>)),
+
+	/// Takes a reference to a context, erased to a pointer, of the type whose id is returned by `context`
+	WithContext {
+		init: fn(*const ()) -> T,
+		context: fn() -> core::any::TypeId,
+		context_name: fn() -> &'static str,
+	},
+
+	#[cfg(feature = "std")]
+	Async(fn() -> AsyncInit<T>),
+}
+
+/// A reference to the context given to `MagicStatic::init_with`, type-erased so that it can be threaded through initialization.
+#[derive(Clone, Copy)]
+struct Context {
+	value: *const (),
+	type_id: core::any::TypeId,
+}
+
+/// The future returned by the initializer of an async magic static.
+#[cfg(feature = "std")]
+pub type AsyncInit<T> = core::pin::Pin<std::boxed::Box<dyn core::future::Future<Output = T>>>;
+
+impl<T> MagicStatic<T> {
+	/// Creates a new, uninitialized magic static which will be initialized using `init`.
+	///
+	/// `init` is a function pointer, so it can be a closure only if it doesn't capture anything.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// use magic_static::MagicStatic;
+	///
+	/// static MAGIC: MagicStatic<usize> = MagicStatic::new(|| 42);
+	///
+	/// fn main() {
+	///     magic_static::init! { MAGIC };
+	///     assert_eq!(*MAGIC, 42);
+	/// }
+	/// ```
+	#[inline]
+	pub const fn new(init: fn() -> T) -> Self {
+		Self::named("<unnamed>", init)
+	}
+
+	/// Creates a new, uninitialized magic static which will be initialized using `init`.
+	///
+	/// `name` is used to identify this magic static in diagnostics.
+	#[inline]
+	pub const fn named(name: &'static str, init: fn() -> T) -> Self {
+		Self::with_init(name, Init::Infallible(init))
+	}
+
+	/// Creates a new, uninitialized magic static which will be initialized by awaiting the future returned by `init`.
+	///
+	/// Async magic statics must be initialized by an `async` function annotated with `#[magic_static::main]`. Initializing them any other way will panic.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// use magic_static::MagicStatic;
+	///
+	/// static MAGIC: MagicStatic<usize> = MagicStatic::new_async(|| Box::pin(async { 42 }));
+	///
+	/// #[magic_static::main(MAGIC)]
+	/// async fn run() {
+	///     assert_eq!(*MAGIC, 42);
+	/// }
+	///
+	/// fn main() {
+	///     # fn block_on(future: impl std::future::Future<Output = ()>) {
+	///     #     let waker = std::task::Waker::noop();
+	///     #     let mut future = std::pin::pin!(future);
+	///     #     while future.as_mut().poll(&mut std::task::Context::from_waker(&waker)).is_pending() {}
+	///     # }
+	///     block_on(run());
+	/// }
+	/// ```
+	#[cfg(feature = "std")]
+	#[inline]
+	pub const fn new_async(init: fn() -> AsyncInit<T>) -> Self {
+		Self::named_async("<unnamed>", init)
+	}
+
+	/// Creates a new, uninitialized magic static which will be initialized by awaiting the future returned by `init`.
+	///
+	/// `name` is used to identify this magic static in diagnostics.
+	#[cfg(feature = "std")]
+	#[inline]
+	pub const fn named_async(name: &'static str, init: fn() -> AsyncInit<T>) -> Self {
+		Self::with_init(name, Init::Async(init))
+	}
+
+	/// Creates a new, uninitialized magic static which will be initialized by `init` writing its value in place, so that the value is never
+	/// moved (e.g. a large lookup table, which could overflow a small stack.) See `magic_static::array_from_fn!` for arrays.
+	///
+	/// # Safety
+	///
+	/// `init` must fully initialize the value it is given, unless it panics.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// use magic_static::MagicStatic;
+	///
+	/// static TABLE: MagicStatic<[u8; 4]> = unsafe { MagicStatic::new_in_place(|table| { table.write([1, 2, 3, 4]); }) };
+	///
+	/// fn main() {
+	///     magic_static::init! { TABLE };
+	///     assert_eq!(TABLE[3], 4);
+	/// }
+	/// ```
+	#[inline]
+	pub const unsafe fn new_in_place(init: fn(&mut MaybeUninit<T>)) -> Self {
+		Self::named_in_place("<unnamed>", init)
+	}
+
+	/// Creates a new, uninitialized magic static which will be initialized by `init` writing its value in place.
+	///
+	/// `name` is used to identify this magic static in diagnostics.
+	///
+	/// # Safety
+	///
+	/// See [`MagicStatic::new_in_place`].
+	#[inline]
+	pub const unsafe fn named_in_place(name: &'static str, init: fn(&mut MaybeUninit<T>)) -> Self {
+		Self::with_init(name, Init::InPlace(init))
+	}
+
+	/// Creates a new, uninitialized magic static which will be initialized using `init` and a context only available at runtime (e.g. parsed
+	/// command line arguments), given to [`MagicStatic::init_with`] or `magic_static::init_with!`.
+	///
+	/// Initializing it any other way (including as a dependency, or with `#[magic_static::main]`) will panic, as there is no context to give `init`.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// use magic_static::MagicStatic;
+	///
+	/// struct Args {
+	///     verbose: bool,
+	///     jobs: usize,
+	/// }
+	///
+	/// static VERBOSE: MagicStatic<bool> = MagicStatic::with_context(|args: &Args| args.verbose);
+	/// static JOBS: MagicStatic<usize> = MagicStatic::with_context(|args: &Args| args.jobs.max(1));
+	///
+	/// fn main() {
+	///     let args = Args { verbose: true, jobs: 0 };
+	///     magic_static::init_with! { &args => VERBOSE, JOBS };
+	///
+	///     assert!(*VERBOSE);
+	///     assert_eq!(*JOBS, 1);
+	/// }
+	/// ```
+	#[inline]
+	pub const fn with_context<C: 'static>(init: fn(&C) -> T) -> Self {
+		Self::named_with_context("<unnamed>", init)
+	}
+
+	/// Creates a new, uninitialized magic static which will be initialized using `init` and a context given to [`MagicStatic::init_with`].
+	///
+	/// `name` is used to identify this magic static in diagnostics.
+	#[inline]
+	pub const fn named_with_context<C: 'static>(name: &'static str, init: fn(&C) -> T) -> Self {
+		Self::with_init(name, Init::WithContext {
+			// SAFETY: `&C` and `*const ()` are ABI-compatible, and the pointer is only ever created from a `&C` (see `Context`)
+			init: unsafe { core::mem::transmute::<fn(&C) -> T, fn(*const ()) -> T>(init) },
+			context: core::any::TypeId::of::<C>,
+			context_name: core::any::type_name::<C>,
+		})
+	}
+}
+
+impl<T, E> MagicStatic<T, E> {
+	/// Creates a new, uninitialized magic static which will be initialized using the fallible `init`.
+	///
+	/// Use [`MagicStatic::try_init_checked`] (or `magic_static::init_try!`) to handle the error. Initializing it any other way will panic if `init` fails.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// use magic_static::MagicStatic;
+	///
+	/// static PORT: MagicStatic<u16, core::num::ParseIntError> = MagicStatic::try_new(|| "8080".parse());
+	///
+	/// fn main() -> Result<(), core::num::ParseIntError> {
+	///     magic_static::init_try! { PORT }
+	///     assert_eq!(*PORT, 8080);
+	///     Ok(())
+	/// }
+	/// ```
+	#[inline]
+	pub const fn try_new(init: fn() -> Result<T, E>) -> Self {
+		Self::try_named("<unnamed>", init)
+	}
+
+	/// Creates a new, uninitialized magic static which will be initialized using the fallible `init`.
+	///
+	/// `name` is used to identify this magic static in diagnostics.
+	#[inline]
+	pub const fn try_named(name: &'static str, init: fn() -> Result<T, E>) -> Self {
+		Self::with_init(name, Init::Fallible(init))
+	}
+
+	#[inline]
+	const fn with_init(name: &'static str, init: Init<T, E>) -> Self {
+		Self {
+			#[cfg(not(feature = "bare-metal"))]
+			initialized: core::sync::atomic::AtomicU8::new(0),
+
+			#[cfg(feature = "bare-metal")]
+			initialized: UnsafeCell::new(0),
+
+			value: UnsafeCell::new(MaybeUninit::uninit()),
+			init,
+			name,
+			depends_on: &[],
+
+			#[cfg(all(feature = "unsafe-mut", not(feature = "bare-metal")))]
+			taken_mut: core::sync::atomic::AtomicBool::new(false),
+
+			#[cfg(all(feature = "unsafe-mut", feature = "bare-metal"))]
+			taken_mut: UnsafeCell::new(false),
+		}
+	}
+
+	/// Creates a magic static which has already been initialized with `value`.
+	#[cfg(feature = "serde")]
+	#[inline]
+	fn with_value(value: T) -> Self {
+		fn already_initialized<T>() -> T {
+			unreachable!()
+		}
+
+		let mut magic_static = Self::with_init("<deserialized>", Init::Infallible(already_initialized::<T>));
+		magic_static.write_local(value);
+		magic_static
+	}
+
+	#[doc(hidden)]
+	#[inline]
+	pub const fn __depends_on(mut self, depends_on: &'static [&'static dyn AnyMagicStatic]) -> Self {
+		self.depends_on = depends_on;
+		self
+	}
+
+	#[doc(hidden)]
+	#[inline]
+	#[cfg(not(feature = "require-sync"))]
+	pub const fn __require_sync(self) -> Self {
+		self
+	}
+
+	#[doc(hidden)]
+	#[inline]
+	#[cfg(feature = "require-sync")]
+	/// Fails to compile if the value of the magic static can't be shared between threads.
+	pub const fn __require_sync(self) -> Self
+	where
+		T: Sync,
+	{
+		self
+	}
+
+	/// Returns the name of this magic static, as used in panic messages and diagnostics.
+	///
+	/// This is its identifier for magic statics declared with the macros (unless renamed with `#[magic_static(name = "...")]`), or `"<unnamed>"`
+	/// for those created with [`MagicStatic::new`] and friends.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// # #[macro_use] extern crate magic_static;
+	/// magic_statics! {
+	///     static ref MAGIC: usize = 42;
+	/// }
+	///
+	/// fn main() {
+	///     assert_eq!(MAGIC.name(), "MAGIC");
+	/// }
+	/// ```
+	#[inline]
+	pub const fn name(&self) -> &'static str {
+		self.name
+	}
+
+	/// Returns whether this magic static has been initialized and its value is ready to be accessed.
+	#[inline]
+	#[cfg(not(feature = "bare-metal"))]
+	pub fn is_initialized(&self) -> bool {
+		self.initialized.load(core::sync::atomic::Ordering::Acquire) == 2
+	}
+
+	/// Returns whether this magic static has been initialized and its value is ready to be accessed.
+	#[inline]
+	#[cfg(feature = "bare-metal")]
+	pub fn is_initialized(&self) -> bool {
+		unsafe { *self.initialized.get() == 2 }
+	}
+
+	/// Returns the state of this magic static, e.g. to find out which magic static another thread is stuck initializing.
+	#[inline]
+	pub fn state(&self) -> InitState {
+		match self.raw_state() {
+			0 => InitState::Uninitialized,
+			1 => InitState::Initializing,
+			2 => InitState::Initialized,
+			_ => InitState::Poisoned,
+		}
+	}
+
+	/// Returns the raw state of this magic static: `0` if it is uninitialized, `1` if it is initializing, `2` if it is initialized and `3` if it is
+	/// poisoned.
+	///
+	/// This is lower level than [`MagicStatic::state`], for custom tooling (e.g. debuggers) which reads the state directly.
+	#[inline]
+	pub fn raw_state(&self) -> u8 {
+		#[cfg(not(feature = "bare-metal"))]
+		return self.initialized.load(core::sync::atomic::Ordering::Acquire);
+
+		#[cfg(feature = "bare-metal")]
+		unsafe {
+			*self.initialized.get()
+		}
+	}
+
+	/// Returns a reference to the value of this magic static, or `None` if it hasn't been initialized yet.
+	///
+	/// Unlike dereferencing, this never panics and is always sound to call: the magic static only counts as initialized once its initializer has
+	/// written its value, so this returns `None` while its initializer is running.
+	#[inline]
+	pub fn get(&'static self) -> Option<&'static T> {
+		if self.is_initialized() {
+			Some(unsafe { &*(&*self.value.get()).as_ptr() })
+		} else {
+			None
+		}
+	}
+
+	/// Like [`MagicStatic::get`], but tells a poisoned magic static apart from one that just hasn't been initialized yet, so the caller can
+	/// decide whether to retry or give up.
+	///
+	/// This follows [`MagicStatic::state`]: [`InitState::Initialized`] gives `Ok(Some(_))`, [`InitState::Poisoned`] gives `Err(Poisoned)` and
+	/// anything else gives `Ok(None)`.
+	///
+	/// ```rust
+	/// use magic_static::MagicStatic;
+	///
+	/// static READY: MagicStatic<u32> = MagicStatic::new(|| 42);
+	/// static BROKEN: MagicStatic<u32> = MagicStatic::new(|| panic!("no config"));
+	///
+	/// assert_eq!(READY.try_get(), Ok(None));
+	/// magic_static::init! { READY };
+	/// assert_eq!(READY.try_get(), Ok(Some(&42)));
+	///
+	/// assert!(std::panic::catch_unwind(|| magic_static::init! { BROKEN }).is_err());
+	/// assert_eq!(BROKEN.try_get(), Err(magic_static::Poisoned));
+	/// assert_eq!(BROKEN.get(), None);
+	/// ```
+	#[inline]
+	pub fn try_get(&'static self) -> Result<Option<&'static T>, Poisoned> {
+		match self.state() {
+			InitState::Initialized => Ok(Some(unsafe { &*(&*self.value.get()).as_ptr() })),
+			InitState::Poisoned => Err(Poisoned),
+			InitState::Uninitialized | InitState::Initializing => Ok(None),
+		}
+	}
+
+	/// Returns a reference to the value of this magic static, initializing it first if it hasn't been initialized yet.
+	///
+	/// This behaves like a lazily initialized static, for code which can't guarantee that the magic static was initialized beforehand
+	/// (e.g. a library used without `#[magic_static::main]`.) Dereferencing doesn't do this, and stays free of any checks in release builds.
+	///
+	/// # Panics
+	///
+	/// Panics if the magic static is [poisoned](MagicStatic#poisoning), if its initializer is fallible and fails, or if it is async.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// use magic_static::MagicStatic;
+	///
+	/// static MAGIC: MagicStatic<usize> = MagicStatic::new(|| 42);
+	///
+	/// fn main() {
+	///     assert_eq!(MAGIC.get(), None);
+	///     assert_eq!(*MAGIC.get_or_init(), 42);
+	///     assert_eq!(MAGIC.get(), Some(&42));
+	/// }
+	/// ```
+	#[inline]
+	pub fn get_or_init(&'static self) -> &'static T
+	where
+		Self: Sync,
+	{
+		if !self.is_initialized() {
+			self.__init();
+		}
+		unsafe { &*(&*self.value.get()).as_ptr() }
+	}
+
+	/// Returns a reference to the value of this magic static with the full `'static` lifetime, e.g. to store it in another structure.
+	///
+	/// This is the same as `&**MAGIC`, including the check that it has been initialized.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// use magic_static::MagicStatic;
+	///
+	/// static GREETING: MagicStatic<String> = MagicStatic::new(|| "Hello".to_string());
+	///
+	/// struct Greeter {
+	///     greeting: &'static str,
+	/// }
+	///
+	/// fn main() {
+	///     magic_static::init! { GREETING };
+	///
+	///     let greeter = Greeter { greeting: GREETING.get_static() };
+	///     assert_eq!(greeter.greeting, "Hello");
+	/// }
+	/// ```
+	#[inline]
+	pub fn get_static(&'static self) -> &'static T {
+		self.debug_assert_initialized();
+		unsafe { &*(&*self.value.get()).as_ptr() }
+	}
+
+	/// Returns a reference to part of the value of this magic static with the full `'static` lifetime, e.g. to hand out a single field of a large
+	/// configuration to other modules.
+	///
+	/// This checks that it has been initialized the same way dereferencing it does.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// # #[macro_use] extern crate magic_static;
+	/// struct Config {
+	///     name: String,
+	///     ports: Vec<u16>,
+	/// }
+	///
+	/// magic_statics! {
+	///     static ref CONFIG: Config = Config { name: "server".to_string(), ports: vec![80, 443] };
+	/// }
+	///
+	/// fn main() {
+	///     magic_static::init! { CONFIG };
+	///
+	///     let name: &'static str = CONFIG.project(|config| config.name.as_str());
+	///     let ports: &'static [u16] = CONFIG.project(|config| &config.ports);
+	///     assert_eq!((name, ports), ("server", &[80, 443][..]));
+	/// }
+	/// ```
+	#[inline]
+	pub fn project<U: ?Sized>(&'static self, f: impl FnOnce(&'static T) -> &'static U) -> &'static U {
+		f(self.get_static())
+	}
+
+	/// Returns a clone of the value of this magic static, e.g. to pass it to generic code expecting a `T: Clone`. Shorthand for `(*MAGIC).clone()`.
+	///
+	/// This checks that it has been initialized the same way dereferencing it does.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// # #[macro_use] extern crate magic_static;
+	/// magic_statics! {
+	///     static ref ALLOWED: Vec<&'static str> = vec!["alice", "bob"];
+	/// }
+	///
+	/// fn main() {
+	///     magic_static::init! { ALLOWED };
+	///
+	///     let mut allowed = ALLOWED.clone_inner();
+	///     allowed.push("carol");
+	///     assert_eq!((ALLOWED.len(), allowed.len()), (2, 3));
+	/// }
+	/// ```
+	#[inline]
+	pub fn clone_inner(&'static self) -> T
+	where
+		T: Clone,
+	{
+		self.get_static().clone()
+	}
+
+	/// Parses the value of this magic static, for the common case of configuration read as a string. Shorthand for `(*MAGIC).as_ref().parse()`.
+	///
+	/// This checks that it has been initialized the same way dereferencing it does.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// # #[macro_use] extern crate magic_static;
+	/// magic_statics! {
+	///     static ref RAW_PORT: String = "8080".to_string();
+	/// }
+	///
+	/// fn main() {
+	///     magic_static::init! { RAW_PORT };
+	///
+	///     assert_eq!(RAW_PORT.parse::<u16>(), Ok(8080));
+	///     assert!(RAW_PORT.parse::<bool>().is_err());
+	/// }
+	/// ```
+	#[inline]
+	pub fn parse<U: core::str::FromStr>(&'static self) -> Result<U, U::Err>
+	where
+		T: AsRef<str>,
+	{
+		self.get_static().as_ref().parse()
+	}
+
+	/// Returns a raw pointer to the value of this magic static, whether or not it has been initialized.
+	///
+	/// The pointer is always valid for the lifetime of the program (e.g. to be stored across an FFI boundary), but **must not be dereferenced before the
+	/// magic static has been initialized**, or after it has been [reset](MagicStatic::reset).
+	#[inline]
+	pub fn as_ptr(&'static self) -> *const T {
+		self.value.get().cast::<T>()
+	}
+
+	/// Returns a raw mutable pointer to the value of this magic static, whether or not it has been initialized.
+	///
+	/// See [`MagicStatic::as_ptr`]. Writing through the pointer is subject to the same requirements as [`MagicStatic::get_mut_unchecked`].
+	///
+	/// Requires the `unsafe-mut` feature flag.
+	#[cfg(feature = "unsafe-mut")]
+	#[inline]
+	pub fn as_mut_ptr(&'static self) -> *mut T {
+		self.value.get().cast::<T>()
+	}
+
+	/// Calls `f` with a reference to the value of this magic static, returning its result.
+	///
+	/// Like dereferencing, this doesn't check whether the magic static has been initialized in release builds.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// # #[macro_use] extern crate magic_static;
+	/// magic_statics! {
+	///     static ref PRIMES: Vec<usize> = vec![2, 3, 5, 7];
+	/// }
+	///
+	/// #[magic_static::main(PRIMES)]
+	/// fn main() {
+	///     assert_eq!(PRIMES.with(|primes| primes.iter().sum::<usize>()), 17);
+	/// }
+	/// ```
+	#[cfg_attr(debug_assertions, inline)]
+	#[cfg_attr(not(debug_assertions), inline(always))]
+	pub fn with<R>(&'static self, f: impl FnOnce(&T) -> R) -> R {
+		f(self)
+	}
+
+	/// Initializes a magic static that isn't a `static` (e.g. one on the stack in a test), returning whether it was initialized by this call.
+	///
+	/// Magic statics are normally initialized through a `&'static` reference, which is what `init!` and `#[magic_static::main]` require. This takes
+	/// `&mut self` instead, so the magic static can be used without a `static`, and its value can then be recovered with [`MagicStatic::into_inner`].
+	///
+	/// Its dependencies are initialized first, as usual.
+	///
+	/// # Panics
+	///
+	/// Panics if the initializer is fallible and fails, or if it is async.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// use magic_static::MagicStatic;
+	///
+	/// let mut magic = MagicStatic::new(|| vec![1, 2, 3]);
+	/// assert!(magic.init_local());
+	/// assert!(!magic.init_local());
+	/// assert_eq!(magic.len(), 3);
+	/// assert_eq!(magic.into_inner(), Some(vec![1, 2, 3]));
+	///
+	/// assert_eq!(MagicStatic::new(|| 42).into_inner(), None);
+	/// ```
+	pub fn init_local(&mut self) -> bool {
+		if self.is_initialized() {
+			return false;
+		}
+
+		for dependency in self.depends_on {
+			dependency.__init_any();
+		}
+
+		let value = match self.init {
+			Init::Infallible(init) => init(),
+			Init::Fallible(init) => match init() {
+				Ok(value) => value,
+				Err(_) => init_failed(self.name),
+			},
+
+			Init::InPlace(init) => {
+				init(self.value.get_mut());
+				self.set_initialized_local();
+				return true;
+			},
+
+			Init::WithContext { context_name, .. } => context_required(self.name, context_name()),
+
+			#[cfg(feature = "std")]
+			Init::Async(_) => async_init_required(self.name),
+		};
+
+		self.write_local(value);
+		true
+	}
+
+	#[inline]
+	fn write_local(&mut self, value: T) {
+		self.value.get_mut().write(value);
+		self.set_initialized_local();
+	}
+
+	#[inline]
+	fn set_initialized_local(&mut self) {
+		*self.initialized.get_mut() = 2;
+	}
+
+	/// Consumes a magic static that isn't a `static`, returning its value, or `None` if it hasn't been initialized.
+	///
+	/// See [`MagicStatic::init_local`].
+	pub fn into_inner(self) -> Option<T> {
+		let this = core::mem::ManuallyDrop::new(self);
+		if this.is_initialized() {
+			Some(unsafe { core::ptr::read((&*this.value.get()).as_ptr()) })
+		} else {
+			None
+		}
+	}
+
+	/// Returns a mutable reference to the value of this magic static.
+	///
+	/// This is how magic statics declared with `#[magic_static] static mut` are mutated.
+	///
+	/// Requires the `unsafe-mut` feature flag.
+	///
+	/// # Safety
+	///
+	/// **This is only sound in single-threaded contexts!** The caller must guarantee that the magic static has been initialized, and that no other references to its value exist while the returned reference is alive.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// #[magic_static::magic_static]
+	/// static mut BUFFER: [u8; 16] = {
+	///     let mut buffer = [0; 16];
+	///     for (i, byte) in buffer.iter_mut().enumerate() {
+	///         *byte = i as u8;
+	///     }
+	///     buffer
+	/// };
+	///
+	/// #[magic_static::main(BUFFER)]
+	/// fn main() {
+	///     unsafe { BUFFER.get_mut_unchecked()[0] = 42 };
+	///     assert_eq!(BUFFER[..2], [42, 1]);
+	/// }
+	/// ```
+	#[cfg(feature = "unsafe-mut")]
+	#[inline]
+	#[allow(clippy::mut_from_ref)]
+	pub unsafe fn get_mut_unchecked(&'static self) -> &'static mut T {
+		self.debug_assert_initialized();
+		&mut *(&mut *self.value.get()).as_mut_ptr()
+	}
+
+	/// Returns a mutable reference to the value of this magic static, or `None` if it hasn't been initialized yet.
+	///
+	/// This is the checked counterpart to [`MagicStatic::get_mut_unchecked`], for single-threaded code which can't be sure that the magic static has been initialized.
+	///
+	/// Requires the `unsafe-mut` feature flag.
+	///
+	/// # Safety
+	///
+	/// **This is only sound in single-threaded contexts!** The caller must guarantee that no other references to the value of this magic static exist while the
+	/// returned reference is alive. Checking whether it has been initialized can't make this safe, as magic statics are always `Sync`.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// use magic_static::MagicStatic;
+	///
+	/// static COUNTER: MagicStatic<usize> = MagicStatic::new(|| 0);
+	///
+	/// fn main() {
+	///     assert!(unsafe { COUNTER.try_get_mut() }.is_none());
+	///
+	///     magic_static::init! { COUNTER };
+	///     if let Some(counter) = unsafe { COUNTER.try_get_mut() } {
+	///         *counter += 1;
+	///     }
+	///     assert_eq!(*COUNTER, 1);
+	/// }
+	/// ```
+	#[cfg(feature = "unsafe-mut")]
+	#[inline]
+	#[allow(clippy::mut_from_ref)]
+	pub unsafe fn try_get_mut(&'static self) -> Option<&'static mut T> {
+		if self.is_initialized() {
+			Some(&mut *(&mut *self.value.get()).as_mut_ptr())
+		} else {
+			None
+		}
+	}
+
+	/// Returns a mutable reference to the value of this magic static the first time it is called after the magic static has been initialized, and
+	/// `None` ever after (or if it hasn't been initialized yet, in which case a later call can still take it.)
+	///
+	/// This is for the "initialize, configure, then freeze" pattern: startup code finishes configuring the value through the returned reference, and the
+	/// rest of the program only ever reads it. The reference is handed out at most once over the lifetime of the magic static, even if it is deinitialized
+	/// and initialized again.
+	///
+	/// Requires the `unsafe-mut` feature flag.
+	///
+	/// # Safety
+	///
+	/// **This is only sound in single-threaded contexts!** Handing the reference out once only guarantees that this doesn't create two mutable references
+	/// itself. The caller must also guarantee that:
+	///
+	/// * No other references to the value exist while the returned reference is alive, including ones obtained by dereferencing the magic static
+	///   beforehand, and that the value isn't read through the magic static until the returned reference is no longer used.
+	/// * The magic static isn't replaced, reset or deinitialized while the returned reference is alive, which would leave it dangling.
+	/// * No other mutable access (e.g. [`MagicStatic::get_mut_unchecked`]) happens while the returned reference is alive.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// use magic_static::MagicStatic;
+	///
+	/// static PLUGINS: MagicStatic<Vec<&str>> = MagicStatic::new(|| vec!["core"]);
+	///
+	/// fn main() {
+	///     magic_static::init! { PLUGINS };
+	///
+	///     // Configure...
+	///     let plugins = unsafe { PLUGINS.take_mut_once() }.unwrap();
+	///     plugins.push("extra");
+	///
+	///     // ...then freeze
+	///     assert!(unsafe { PLUGINS.take_mut_once() }.is_none());
+	///     assert_eq!(*PLUGINS, ["core", "extra"]);
+	/// }
+	/// ```
+	#[cfg(feature = "unsafe-mut")]
+	#[inline]
+	#[allow(clippy::mut_from_ref)]
+	pub unsafe fn take_mut_once(&'static self) -> Option<&'static mut T> {
+		if !self.is_initialized() {
+			return None;
+		}
+
+		#[cfg(not(feature = "bare-metal"))]
+		let taken = self.taken_mut.swap(true, core::sync::atomic::Ordering::AcqRel);
+
+		#[cfg(feature = "bare-metal")]
+		let taken = core::mem::replace(&mut *self.taken_mut.get(), true);
+
+		if taken {
+			None
+		} else {
+			Some(&mut *(&mut *self.value.get()).as_mut_ptr())
+		}
+	}
+
+	/// Initializes this magic static, returning an error if it has already been initialized.
+	///
+	/// If another thread is currently initializing this magic static, this will wait for it to finish and then return an error.
+	///
+	/// # Panics
+	///
+	/// Panics if the magic static is [poisoned](MagicStatic#poisoning), or if its initializer is fallible and fails.
+	///
+	/// # Safety
+	///
+	/// See the safety section of `magic_statics!`.
+	#[inline]
+	pub fn try_init(&'static self) -> Result<(), AlreadyInitialized>
+	where
+		Self: Sync,
+	{
+		match self.initialize() {
+			Ok(true) => Ok(()),
+			Ok(false) => Err(AlreadyInitialized),
+			Err(_) => init_failed(self.name),
+		}
+	}
+
+	/// Initializes this magic static if it isn't already, giving `context` to its initializer if it was created with [`MagicStatic::with_context`],
+	/// and returns whether it was initialized by this call. Magic statics which don't take a context are initialized as usual.
+	///
+	/// `context` is only borrowed while the initializer runs, so the value of the magic static can't borrow from it.
+	///
+	/// # Panics
+	///
+	/// Panics if the initializer takes a context of a different type than `C`, if the magic static is [poisoned](MagicStatic#poisoning), or if its
+	/// initializer is fallible and fails.
+	///
+	/// # Safety
+	///
+	/// See the safety section of `magic_statics!`.
+	#[inline]
+	pub fn init_with<C: 'static>(&'static self, context: &C) -> bool
+	where
+		Self: Sync,
+	{
+		let context = Context {
+			value: context as *const C as *const (),
+			type_id: core::any::TypeId::of::<C>(),
+		};
+
+		match self.poll_initialize(true, Some(context)) {
+			Ok(initialized) => initialized == Some(true),
+			Err(_) => init_failed(self.name),
+		}
+	}
+
+	/// Blocks the current thread until this magic static has been initialized by another thread, returning immediately if it already has been.
+	///
+	/// Unlike initializing it, this never runs its initializer, so a thread which only reads the magic static can't accidentally become the one which
+	/// initializes it. If the initializer of a fallible magic static fails, this keeps waiting for it to be initialized successfully.
+	///
+	/// # Panics
+	///
+	/// Panics if the magic static is (or becomes) [poisoned](MagicStatic#poisoning).
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// # #[macro_use] extern crate magic_static;
+	/// magic_statics! {
+	///     static ref CONFIG: String = "loaded".to_string();
+	/// }
+	///
+	/// fn main() {
+	///     let reader = std::thread::spawn(|| {
+	///         CONFIG.wait_initialized();
+	///         CONFIG.len()
+	///     });
+	///
+	///     magic_static::init! { CONFIG };
+	///     assert_eq!(reader.join().unwrap(), 6);
+	/// }
+	/// ```
+	#[cfg(all(feature = "std", not(feature = "bare-metal")))]
+	pub fn wait_initialized(&'static self) {
+		if !self.is_initialized() && crate::wait::wait_until_initialized(&self.initialized) == 3 {
+			poisoned(self.name);
+		}
+	}
+
+	/// Initializes this magic static if it isn't already, **without waiting** if another thread (or task) is currently initializing it.
+	///
+	/// Returns `true` once the magic static has been initialized (by this call or before), and `false` if its initialization is in progress elsewhere,
+	/// so that a cooperative scheduler can try again later instead of blocking. With the `bare-metal` feature flag, nothing can be initializing it
+	/// concurrently, so this returns whether it was initialized by this call instead, like `magic_static::init!`.
+	///
+	/// # Panics
+	///
+	/// Panics if the magic static is [poisoned](MagicStatic#poisoning), if its initializer is fallible and fails, or if it is async.
+	///
+	/// # Safety
+	///
+	/// See the safety section of `magic_statics!`.
+	#[inline]
+	pub fn poll_init(&'static self) -> bool
+	where
+		Self: Sync,
+	{
+		match self.poll_initialize(false, None) {
+			#[cfg(not(feature = "bare-metal"))]
+			Ok(initialized) => initialized.is_some(),
+
+			#[cfg(feature = "bare-metal")]
+			Ok(initialized) => initialized == Some(true),
+
+			Err(_) => init_failed(self.name),
+		}
+	}
+
+	/// Initializes this magic static, returning the error of its initializer if it fails.
+	///
+	/// Does nothing if the magic static has already been initialized. If the initializer fails, the magic static is left uninitialized.
+	///
+	/// # Panics
+	///
+	/// Panics if the magic static is [poisoned](MagicStatic#poisoning).
+	///
+	/// # Safety
+	///
+	/// See the safety section of `magic_statics!`.
+	#[inline]
+	pub fn try_init_checked(&'static self) -> Result<(), E>
+	where
+		Self: Sync,
+	{
+		self.initialize().map(|_| ())
+	}
+
+	/// Initializes this magic static if it isn't already, returning whether it was initialized by this call.
+	#[inline]
+	fn initialize(&'static self) -> Result<bool, E>
+	where
+		Self: Sync,
+	{
+		self.poll_initialize(true, None).map(|initialized| initialized == Some(true))
+	}
+
+	/// Initializes this magic static if it isn't already, returning whether it was initialized by this call.
+	///
+	/// If another thread is currently initializing this magic static, this waits for it to finish if `wait` is set, and returns `None` otherwise.
+	#[inline]
+	fn poll_initialize(&'static self, wait: bool, context: Option<Context>) -> Result<Option<bool>, E>
+	where
+		Self: Sync,
+	{
+		let initialized = self.initialize_with_context(wait, context)?;
+
+		#[cfg(any(feature = "std", feature = "alloc"))]
+		if initialized == Some(true) {
+			crate::teardown::register(self);
+		}
+
+		Ok(initialized)
+	}
+
+	/// Initializes this magic static without registering it for teardown, which thread-local magic statics mustn't be. See `poll_initialize`.
+	#[inline]
+	#[cfg(feature = "std")]
+	pub(crate) fn initialize_unregistered(&'static self, wait: bool) -> Result<Option<bool>, E> {
+		self.initialize_with_context(wait, None)
+	}
+
+	#[inline]
+	fn initialize_with_context(&'static self, wait: bool, context: Option<Context>) -> Result<Option<bool>, E> {
+		#[cfg(feature = "std")]
+		if let Init::Async(_) = self.init {
+			async_init_required(self.name);
+		}
+
+		// Checked before claiming the magic static, so that a missing context doesn't poison it
+		if let Init::WithContext { context: expected, context_name, .. } = self.init {
+			if !matches!(context, Some(context) if context.type_id == expected()) && !self.is_initialized() {
+				context_required(self.name, context_name());
+			}
+		}
+
+		// Interrupts must not observe the magic static between claiming it and storing its value
+		#[cfg(feature = "critical-section")]
+		return critical_section::with(|_| self.initialize_uninterrupted(wait, context));
+
+		#[cfg(not(feature = "critical-section"))]
+		self.initialize_uninterrupted(wait, context)
+	}
+
+	#[inline]
+	fn initialize_uninterrupted(&'static self, wait: bool, context: Option<Context>) -> Result<Option<bool>, E> {
+		match self.begin_initializing(wait) {
+			Some(true) => {},
+			claimed => return Ok(claimed),
+		}
+
+		// Poisons the magic static if the initializer panics
+		let poison = PoisonOnUnwind(&self.initialized);
+		let result = unsafe { self.run_init(context) };
+		core::mem::forget(poison);
+
+		unsafe { self.store_result(result) }.map(Some)
+	}
+
+	/// Initializes this magic static if it isn't already, awaiting its initializer if it is async.
+	async fn initialize_async(&'static self) -> Result<bool, E>
+	where
+		Self: Sync,
+	{
+		#[cfg(feature = "std")]
+		if let Init::Async(init) = self.init {
+			if self.begin_initializing(true) != Some(true) {
+				return Ok(false);
+			}
+
+			// Poisons the magic static if the initializer panics or its future is dropped
+			let poison = PoisonOnUnwind(&self.initialized);
+
+			self.init_dependencies();
+
+			#[cfg(feature = "profiling")]
+			let start = std::time::Instant::now();
+
+			let value = init().await;
+
+			#[cfg(feature = "profiling")]
+			crate::profiling::record(self.name, start.elapsed());
+
+			core::mem::forget(poison);
+
+			let initialized = unsafe {
+				(&mut *self.value.get()).write(value);
+				self.store_result(Ok(()))
+			};
+
+			#[cfg(any(feature = "std", feature = "alloc"))]
+			crate::teardown::register(self);
+
+			return initialized;
+		}
+
+		self.initialize()
+	}
+
+	/// Claims this magic static for initialization, returning `Some(false)` if it has already been initialized.
+	///
+	/// If another thread is currently initializing this magic static, this will wait for it to finish first if `wait` is set, and return `None` otherwise.
+	#[inline]
+	#[cfg_attr(feature = "bare-metal", allow(unused_variables))]
+	fn begin_initializing(&'static self, wait: bool) -> Option<bool> {
+		#[cfg(not(feature = "bare-metal"))]
+		loop {
+			match self.initialized.compare_exchange(0, 1, CLAIM_SUCCESS, CLAIM_FAILURE) {
+				Ok(_) => return Some(true),
+
+				Err(1) if !wait => return None,
+
+				Err(1) => {
+					// Wait, then try again in case the initializer failed
+					#[cfg(feature = "std")]
+					crate::wait::wait_while_initializing(&self.initialized, self.name);
+
+					// Without `std` there is no scheduler to yield to or park on, so busy-wait
+					#[cfg(not(feature = "std"))]
+					while self.initialized.load(core::sync::atomic::Ordering::Acquire) == 1 {
+						core::hint::spin_loop();
+					}
+				},
+
+				Err(2) => return Some(false),
+
+				Err(3) => poisoned(self.name),
+
+				Err(code) => unreachable!("{:?}", code)
+			}
+		}
+
+		// Nothing else can be initializing the magic static, so it is being initialized by its own initializer (or an interrupt handler
+		// which interrupted it), which would wait forever
+		#[cfg(feature = "bare-metal")]
+		unsafe {
+			match *self.initialized.get() {
+				0 => {
+					*self.initialized.get() = 1;
+					Some(true)
+				},
+
+				1 if !wait => None,
+
+				1 => initialized_recursively(self.name),
+
+				2 => Some(false),
+
+				3 => poisoned(self.name),
+
+				code => unreachable!("{:?}", code)
+			}
+		}
+	}
+
+	/// Finishes initializing the magic static once its initializer has written its value, or resets it to its uninitialized state if it failed.
+	///
+	/// # Safety
+	///
+	/// Must only be called after `begin_initializing` returned `Some(true)`.
+	#[inline]
+	unsafe fn store_result(&'static self, result: Result<(), E>) -> Result<bool, E> {
+		match result {
+			Ok(()) => {
+				finish_initializing(&self.initialized, 2);
+				Ok(true)
+			},
+
+			Err(err) => {
+				finish_initializing(&self.initialized, 0);
+				Err(err)
+			}
+		}
+	}
+
+	#[inline]
+	fn init_dependencies(&'static self) {
+		if !self.depends_on.is_empty() {
+			// Starts from this magic static's own address and name (rather than a `dyn AnyMagicStatic`), as it needn't be `Sync`
+			let path = DependencyPath { address: self as *const Self as *const (), name: self.name, parent: None };
+			check_dependency_cycles(&path, self.depends_on);
+			for dependency in self.depends_on {
+				dependency.__init_any();
+			}
+		}
+	}
+
+	/// Runs the initializer, writing its value into this magic static.
+	///
+	/// # Safety
+	///
+	/// This magic static must not hold a value, and `context` must match the initializer if it takes one.
+	#[inline]
+	unsafe fn run_init(&'static self, context: Option<Context>) -> Result<(), E> {
+		// In-place initializers never go near a function holding a `T` (even one they don't use, as unoptimized builds reserve stack for every
+		// local of a function), so that even a huge value never passes through the stack
+		match self.init {
+			Init::InPlace(init) => {
+				self.run_init_with(|| init(&mut *self.value.get()));
+				Ok(())
+			},
+
+			_ => self.run_init_write(context),
+		}
+	}
+
+	/// Runs an initializer which returns its value, and writes it into this magic static.
+	///
+	/// # Safety
+	///
+	/// See `run_init`.
+	#[inline]
+	unsafe fn run_init_write(&'static self, context: Option<Context>) -> Result<(), E> {
+		let value = self.run_init_value(context)?;
+		(&mut *self.value.get()).write(value);
+		Ok(())
+	}
+
+	/// Runs the initializer, returning its value. It must not be an in-place initializer.
+	///
+	/// # Safety
+	///
+	/// `context` must match the initializer if it takes one.
+	#[inline]
+	unsafe fn run_init_value(&'static self, context: Option<Context>) -> Result<T, E> {
+		self.run_init_with(|| match self.init {
+			Init::Infallible(init) => Ok(init()),
+			Init::Fallible(init) => init(),
+			Init::InPlace(_) => unreachable!(),
+
+			Init::WithContext { init, .. } => Ok(init(context.expect("context was checked before initializing").value)),
+
+			#[cfg(feature = "std")]
+			Init::Async(_) => unreachable!(),
+		})
+	}
+
+	/// Runs `init` as the initializer of this magic static, after initializing its dependencies.
+	#[inline]
+	fn run_init_with<R>(&'static self, init: impl FnOnce() -> R) -> R {
+		self.init_dependencies();
+
+		#[cfg(all(debug_assertions, feature = "std"))]
+		let _initializing = Initializing::enter(self.name);
+
+		#[cfg(feature = "profiling")]
+		return crate::profiling::timed(self.name, init);
+
+		#[cfg(not(feature = "profiling"))]
+		init()
+	}
+
+	#[inline]
+	fn debug_assert_initialized(&self) {
+		#[cfg(any(debug_assertions, feature = "checked-release"))]
+		self.assert_initialized();
+	}
+
+	/// Panics if this magic static hasn't been initialized, whatever the build profile.
+	#[inline]
+	fn assert_initialized(&self) {
+		if !self.is_initialized() {
+			#[cfg(all(debug_assertions, feature = "std"))]
+			if let Some(initializing) = Initializing::current() {
+				panic!("The magic static `{}` was accessed during the initialization of `{}` before it was initialized! Make sure it is initialized first, e.g. with `depends_on`.", self.name, initializing);
+			}
+
+			not_initialized(self.name);
+		}
+	}
+
+	#[doc(hidden)]
+	#[inline]
+	/// Returns whether the magic static was initialized by this call.
+	pub fn __init(&'static self) -> bool
+	where
+		Self: Sync,
+	{
+		self.try_init().is_ok()
+	}
+
+	#[doc(hidden)]
+	#[inline]
+	pub fn __init_strict(&'static self)
+	where
+		Self: Sync,
+	{
+		if self.try_init().is_err() {
+			already_initialized(self.name);
+		}
+	}
+
+	#[doc(hidden)]
+	#[inline]
+	pub fn __try_init_strict(&'static self) -> Result<(), E>
+	where
+		Self: Sync,
+	{
+		match self.initialize() {
+			Ok(true) => Ok(()),
+			Ok(false) => already_initialized(self.name),
+			Err(err) => Err(err),
+		}
+	}
+
+	#[doc(hidden)]
+	pub async fn __init_async_strict(&'static self)
+	where
+		Self: Sync,
+	{
+		if !self.__init_async().await {
+			already_initialized(self.name);
+		}
+	}
+
+	#[doc(hidden)]
+	#[cfg(feature = "startup")]
+	#[inline]
+	/// Async magic statics can't be initialized before `main`, so they are left to `#[magic_static::main]`.
+	pub fn __init_startup(&'static self)
+	where
+		Self: Sync,
+	{
+		#[cfg(feature = "std")]
+		if let Init::Async(_) = self.init {
+			return;
+		}
+
+		self.__init();
+	}
+
+	#[doc(hidden)]
+	/// Returns whether the magic static was initialized by this call.
+	pub async fn __init_async(&'static self) -> bool
+	where
+		Self: Sync,
+	{
+		match self.initialize_async().await {
+			Ok(initialized) => initialized,
+			Err(_) => init_failed(self.name),
+		}
+	}
+
+	/// Drops the value of this magic static (if it has been initialized) and resets it to its uninitialized state, so that the next initialization runs its initializer again.
+	///
+	/// Does nothing if the magic static hasn't been initialized.
+	///
+	/// # Safety
+	///
+	/// The caller must guarantee that there are no outstanding references to the value of this magic static, and that it is not accessed concurrently.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// use magic_static::MagicStatic;
+	///
+	/// static MAGIC: MagicStatic<String> = MagicStatic::new(|| String::from("magic"));
+	///
+	/// fn main() {
+	///     magic_static::init! { MAGIC };
+	///     assert_eq!(*MAGIC, "magic");
+	///
+	///     unsafe { MAGIC.reset() };
+	///     assert!(!MAGIC.is_initialized());
+	///
+	///     magic_static::init! { MAGIC };
+	///     assert_eq!(*MAGIC, "magic");
+	/// }
+	/// ```
+	#[inline]
+	pub unsafe fn reset(&'static self)
+	where
+		Self: Sync,
+	{
+		#[cfg(not(feature = "bare-metal"))]
+		if self.initialized.compare_exchange(2, 1, CLAIM_SUCCESS, CLAIM_FAILURE).is_ok() {
+			core::ptr::drop_in_place((&mut *self.value.get()).as_mut_ptr());
+			finish_initializing(&self.initialized, 0);
+
+			#[cfg(any(feature = "std", feature = "alloc"))]
+			crate::teardown::unregister(self);
+		}
+
+		#[cfg(feature = "bare-metal")]
+		if *self.initialized.get() == 2 {
+			*self.initialized.get() = 0;
+			core::ptr::drop_in_place((&mut *self.value.get()).as_mut_ptr());
+
+			#[cfg(any(feature = "std", feature = "alloc"))]
+			crate::teardown::unregister(self);
+		}
+	}
+
+	/// Runs the initializer of this magic static again and replaces its value with the result, dropping the previous value.
+	///
+	/// Unlike [`reset`](MagicStatic::reset), the magic static never returns to an uninitialized state: the new value is computed before the
+	/// previous one is dropped, so this is useful for reloading cached values (e.g. configuration) on demand. If the magic static hasn't
+	/// been initialized yet, it is simply initialized.
+	///
+	/// Magic statics with an in-place initializer ([`MagicStatic::new_in_place`] or `#[magic_static(unsafe(in_place))]`) are the exception: their
+	/// new value is written where the previous one was, so this drops the previous value first, like [`reset`](MagicStatic::reset), and then
+	/// initializes the magic static again.
+	///
+	/// # Safety
+	///
+	/// This is only sound in single-threaded code. The caller must guarantee that there are no outstanding references to the value of this magic static,
+	/// and that it is not accessed concurrently, including from its own initializer.
+	///
+	/// # Panics
+	///
+	/// Panics if the initializer fails or panics, in which case the previous value is kept, or if the magic static has an async initializer.
+	///
+	/// If the initializer of an in-place magic static panics, the previous value has already been dropped, and the magic static is left
+	/// [poisoned](MagicStatic#poisoning).
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// use magic_static::MagicStatic;
+	/// use std::sync::atomic::{AtomicUsize, Ordering};
+	///
+	/// static RELOADS: AtomicUsize = AtomicUsize::new(0);
+	/// static CONFIG: MagicStatic<usize> = MagicStatic::new(|| RELOADS.fetch_add(1, Ordering::SeqCst));
+	///
+	/// fn main() {
+	///     magic_static::init! { CONFIG };
+	///     assert_eq!(*CONFIG, 0);
+	///
+	///     unsafe { CONFIG.reinit() };
+	///     assert_eq!(*CONFIG, 1);
+	/// }
+	/// ```
+	pub unsafe fn reinit(&'static self)
+	where
+		Self: Sync,
+	{
+		if !self.is_initialized() {
+			if self.initialize().is_err() {
+				init_failed(self.name);
+			}
+			return;
+		}
+
+		#[cfg(feature = "std")]
+		if let Init::Async(_) = self.init {
+			async_init_required(self.name);
+		}
+
+		if let Init::WithContext { context_name, .. } = self.init {
+			context_required(self.name, context_name());
+		}
+
+		// The value can't be written in place while the previous value is still alive
+		if let Init::InPlace(_) = self.init {
+			self.reset();
+			if self.initialize().is_err() {
+				init_failed(self.name);
+			}
+			return;
+		}
+
+		self.reinit_with_value();
+	}
+
+	/// Reinitializes this magic static with the value returned by its initializer, only dropping the previous value once the new one is ready.
+	///
+	/// This is kept out of `reinit` so that reinitializing an in-place magic static never reserves stack for a `T`.
+	///
+	/// # Safety
+	///
+	/// See [`MagicStatic::reinit`].
+	unsafe fn reinit_with_value(&'static self)
+	where
+		Self: Sync,
+	{
+		match self.run_init_value(None) {
+			Ok(value) => drop(self.replace(value)),
+			Err(_) => init_failed(self.name),
+		}
+	}
+
+	/// Replaces the value of this magic static with `value` without running its initializer, returning the previous value if it had been initialized.
+	///
+	/// The magic static is initialized afterwards (even if it was [poisoned](MagicStatic#poisoning)), which makes this useful for substituting mock values in tests.
+	///
+	/// # Safety
+	///
+	/// The caller must guarantee that there are no outstanding references to the value of this magic static, and that it is not accessed concurrently.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// use magic_static::MagicStatic;
+	///
+	/// static ENDPOINT: MagicStatic<&str> = MagicStatic::new(|| "https://example.com");
+	///
+	/// fn main() {
+	///     assert_eq!(unsafe { ENDPOINT.replace("http://localhost") }, None);
+	///     assert_eq!(*ENDPOINT, "http://localhost");
+	///
+	///     // The initializer doesn't run, as the magic static has already been initialized
+	///     magic_static::init! { ENDPOINT };
+	///     assert_eq!(unsafe { ENDPOINT.replace("http://127.0.0.1") }, Some("http://localhost"));
+	/// }
+	/// ```
+	pub unsafe fn replace(&'static self, value: T) -> Option<T>
+	where
+		Self: Sync,
+	{
+		let ptr = (&mut *self.value.get()).as_mut_ptr();
+		let previous = if self.is_initialized() {
+			Some(core::ptr::read(ptr))
+		} else {
+			None
+		};
+
+		ptr.write(value);
+		finish_initializing(&self.initialized, 2);
+
+		#[cfg(any(feature = "std", feature = "alloc"))]
+		if previous.is_none() {
+			crate::teardown::register(self);
+		}
+
+		previous
+	}
+
+	#[doc(hidden)]
+	#[inline]
+	/// # Safety
+	///
+	/// See [`MagicStatic::reset`].
+	pub unsafe fn __deinit(&'static self)
+	where
+		Self: Sync,
+	{
+		self.reset();
+	}
+}
+
+impl<U, E> MagicStatic<Option<U>, E> {
+	/// Returns a reference to the value inside the `Option` held by this magic static, for optional configuration. Shorthand for
+	/// `(*MAGIC).as_ref()`.
+	///
+	/// This checks that it has been initialized the same way dereferencing it does.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// # #[macro_use] extern crate magic_static;
+	/// magic_statics! {
+	///     static ref PROXY: Option<String> = std::env::var("MAGIC_STATIC_EXAMPLE_PROXY").ok();
+	/// }
+	///
+	/// fn main() {
+	///     magic_static::init! { PROXY };
+	///
+	///     match PROXY.as_option() {
+	///         Some(proxy) => println!("Connecting through {}", proxy),
+	///         None => println!("Connecting directly"),
+	///     }
+	/// }
+	/// ```
+	#[inline]
+	pub fn as_option(&'static self) -> Option<&'static U> {
+		self.get_static().as_ref()
+	}
+}
+
 #[doc(hidden)]
+pub trait AnyMagicStatic: Sync {
+	fn __init_any(&'static self);
+	fn __name(&self) -> &'static str;
+	fn __is_initialized_any(&self) -> bool;
+	/// # Safety
+	///
+	/// See [`MagicStatic::reset`].
+	unsafe fn __deinit_any(&'static self);
+	fn __is_lazy(&self) -> bool {
+		false
+	}
+	fn __depends_on(&self) -> &'static [&'static dyn AnyMagicStatic];
+}
+impl<T, E> AnyMagicStatic for MagicStatic<T, E>
+where
+	Self: Sync,
+{
+	#[inline]
+	fn __init_any(&'static self) {
+		self.__init();
+	}
+
+	#[inline]
+	fn __name(&self) -> &'static str {
+		self.name
+	}
+
+	#[inline]
+	fn __is_initialized_any(&self) -> bool {
+		self.is_initialized()
+	}
+
+	#[inline]
+	unsafe fn __deinit_any(&'static self) {
+		self.reset();
+	}
+
+	#[inline]
+	fn __depends_on(&self) -> &'static [&'static dyn AnyMagicStatic] {
+		self.depends_on
+	}
+}
+
+/// An object-safe handle to a magic static of any type, for initializing magic statics from a list assembled at runtime
+/// (e.g. by plugins registering their magic statics when they are loaded.) See [`init_many`].
+pub trait MagicStaticInit: Sync {
+	#[doc(hidden)]
+	fn __init_dyn(&'static self) -> bool;
+
+	/// Returns whether the magic static has been initialized yet.
+	fn is_initialized(&self) -> bool;
+}
+impl<T, E> MagicStaticInit for MagicStatic<T, E>
+where
+	Self: Sync,
+{
+	#[inline]
+	fn __init_dyn(&'static self) -> bool {
+		self.__init()
+	}
+
+	#[inline]
+	fn is_initialized(&self) -> bool {
+		MagicStatic::is_initialized(self)
+	}
+}
+impl<T> MagicStaticInit for LazyMagicStatic<T>
+where
+	MagicStatic<T>: Sync,
+{
+	#[inline]
+	fn __init_dyn(&'static self) -> bool {
+		self.__init()
+	}
+
+	#[inline]
+	fn is_initialized(&self) -> bool {
+		LazyMagicStatic::is_initialized(self)
+	}
+}
+impl<T, const CHECK: bool, E> MagicStaticInit for CheckedMagicStatic<T, CHECK, E>
+where
+	MagicStatic<T, E>: Sync,
+{
+	#[inline]
+	fn __init_dyn(&'static self) -> bool {
+		self.__init()
+	}
+
+	#[inline]
+	fn is_initialized(&self) -> bool {
+		CheckedMagicStatic::is_initialized(self)
+	}
+}
+impl<T: Sync> MagicStaticInit for ConstMagicStatic<T> {
+	#[inline]
+	fn __init_dyn(&'static self) -> bool {
+		false
+	}
+
+	#[inline]
+	fn is_initialized(&self) -> bool {
+		true
+	}
+}
+
+/// Initializes the given magic statics **in slice order**, the same as listing them in `magic_static::init!` would, and returns how many were initialized
+/// by this call.
+///
+/// This is the runtime form of `magic_static::init!`, for lists assembled at runtime (e.g. the subsystems enabled by a configuration file.) Magic statics
+/// which have already been initialized are skipped, and a fallible magic static whose initializer fails panics. Prefer `magic_static::init!` or
+/// `#[magic_static::main]` when the magic statics are known at compile time.
+///
+/// # Example
+///
+/// ```rust
+/// use magic_static::{MagicStatic, MagicStaticInit};
+///
+/// static FIRST: MagicStatic<usize> = MagicStatic::new(|| 1);
+/// static SECOND: MagicStatic<usize> = MagicStatic::new(|| *FIRST + 1);
+///
+/// fn main() {
+///     let mut plugins: Vec<&'static dyn MagicStaticInit> = vec![&FIRST];
+///     plugins.push(&SECOND);
+///
+///     assert_eq!(magic_static::init_many(&plugins), 2);
+///     assert!(plugins.iter().all(|plugin| plugin.is_initialized()));
+///     assert_eq!(*SECOND, 2);
+///
+///     assert_eq!(magic_static::init_many(&plugins), 0);
+/// }
+/// ```
+pub fn init_many(statics: &[&'static dyn MagicStaticInit]) -> usize {
+	statics.iter().filter(|magic_static| magic_static.__init_dyn()).count()
+}
+
+#[doc(hidden)]
+/// The number of magic statics initialized by a module's `magic_static` function.
+///
+/// Hand-written `magic_static` functions (e.g. using `#[magic_static::main]`) return `()`, in which case they count as zero.
+pub trait InitCount {
+	fn __count(self) -> usize;
+}
+impl InitCount for usize {
+	#[inline]
+	fn __count(self) -> usize {
+		self
+	}
+}
+impl InitCount for () {
+	#[inline]
+	fn __count(self) -> usize {
+		0
+	}
+}
+
+struct DependencyPath<'a> {
+	address: *const (),
+	name: &'static str,
+	parent: Option<&'a DependencyPath<'a>>,
+}
+impl<'a> DependencyPath<'a> {
+	#[inline]
+	fn new(magic_static: &'static dyn AnyMagicStatic, parent: Option<&'a DependencyPath<'a>>) -> Self {
+		Self {
+			address: magic_static as *const dyn AnyMagicStatic as *const (),
+			name: magic_static.__name(),
+			parent,
+		}
+	}
+
+	/// Returns whether the magic static at the end of this path is also one of its ancestors.
+	fn repeats(&self) -> bool {
+		let mut ancestor = self.parent;
+		while let Some(node) = ancestor {
+			if node.address == self.address {
+				return true;
+			}
+			ancestor = node.parent;
+		}
+		false
+	}
+
+	fn fmt_cycle(&self, start: *const (), f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		match self.parent {
+			Some(parent) if self.address != start => {
+				parent.fmt_cycle(start, f)?;
+				write!(f, " -> {}", self.name)
+			},
+			_ => f.write_str(self.name),
+		}
+	}
+}
+
+struct DependencyCycle<'a>(&'a DependencyPath<'a>);
+impl core::fmt::Display for DependencyCycle<'_> {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		match self.0.parent {
+			Some(parent) => parent.fmt_cycle(self.0.address, f)?,
+			None => f.write_str(self.0.name)?,
+		}
+		write!(f, " -> {}", self.0.name)
+	}
+}
+
+#[cfg(feature = "auto-register")]
+#[inline]
+fn same_magic_static(a: &'static dyn AnyMagicStatic, b: &'static dyn AnyMagicStatic) -> bool {
+	core::ptr::eq(a as *const dyn AnyMagicStatic as *const (), b as *const dyn AnyMagicStatic as *const ())
+}
+
+/// Walks the dependency graph below the end of a path, panicking if it contains a cycle.
+fn check_dependency_cycles(path: &DependencyPath, depends_on: &'static [&'static dyn AnyMagicStatic]) {
+	if path.repeats() {
+		panic!("Dependency cycle detected between magic statics: {}", DependencyCycle(path));
+	}
+
+	for dependency in depends_on {
+		check_dependency_cycles(&DependencyPath::new(*dependency, Some(path)), dependency.__depends_on());
+	}
+}
+
+#[cfg(all(debug_assertions, feature = "std"))]
+std::thread_local! {
+	static INITIALIZING: core::cell::Cell<Option<&'static str>> = const { core::cell::Cell::new(None) };
+}
+
+/// Tracks which magic static is being initialized by the current thread, so that accessing another uninitialized magic static during its initialization can be reported.
+#[cfg(all(debug_assertions, feature = "std"))]
+struct Initializing(Option<&'static str>);
+
+#[cfg(all(debug_assertions, feature = "std"))]
+impl Initializing {
+	#[inline]
+	fn enter(name: &'static str) -> Self {
+		Self(INITIALIZING.with(|initializing| initializing.replace(Some(name))))
+	}
+
+	#[inline]
+	fn current() -> Option<&'static str> {
+		INITIALIZING.try_with(core::cell::Cell::get).ok().flatten()
+	}
+}
+
+#[cfg(all(debug_assertions, feature = "std"))]
+impl Drop for Initializing {
+	#[inline]
+	fn drop(&mut self) {
+		let _ = INITIALIZING.try_with(|initializing| initializing.set(self.0));
+	}
+}
+
+/// The state of a magic static, see `MagicStatic::raw_state`.
 #[cfg(not(feature = "bare-metal"))]
-macro_rules! __magic_static_initialized {
-	() => {
-		::core::sync::atomic::AtomicU8::new(0)
-	};
+type State = core::sync::atomic::AtomicU8;
+
+#[cfg(feature = "bare-metal")]
+type State = UnsafeCell<u8>;
+
+struct PoisonOnUnwind<'a>(&'a State);
+
+// The orderings of the compare-exchange which claims a magic static for (de)initialization, and of the store which moves it out of the initializing state.
+//
+// `Acquire` and `Release` are enough for the value written by the initializer to be visible to any thread which observes the magic static as
+// initialized, which is all `relaxed-init` relies on. By default, every one of them is `SeqCst` instead.
+#[cfg(all(not(feature = "bare-metal"), not(feature = "relaxed-init")))]
+const CLAIM_SUCCESS: core::sync::atomic::Ordering = core::sync::atomic::Ordering::SeqCst;
+#[cfg(all(not(feature = "bare-metal"), not(feature = "relaxed-init")))]
+const CLAIM_FAILURE: core::sync::atomic::Ordering = core::sync::atomic::Ordering::SeqCst;
+#[cfg(all(not(feature = "bare-metal"), not(feature = "relaxed-init")))]
+const FINISH: core::sync::atomic::Ordering = core::sync::atomic::Ordering::SeqCst;
+
+#[cfg(all(not(feature = "bare-metal"), feature = "relaxed-init"))]
+const CLAIM_SUCCESS: core::sync::atomic::Ordering = core::sync::atomic::Ordering::Acquire;
+#[cfg(all(not(feature = "bare-metal"), feature = "relaxed-init"))]
+const CLAIM_FAILURE: core::sync::atomic::Ordering = core::sync::atomic::Ordering::Acquire;
+#[cfg(all(not(feature = "bare-metal"), feature = "relaxed-init"))]
+const FINISH: core::sync::atomic::Ordering = core::sync::atomic::Ordering::Release;
+
+/// Moves a magic static out of the initializing state, waking up any threads waiting for it.
+#[cfg(not(feature = "bare-metal"))]
+#[inline]
+fn finish_initializing(initialized: &State, state: u8) {
+	initialized.store(state, FINISH);
+
+	#[cfg(feature = "std")]
+	crate::wait::notify();
+}
+
+/// Moves a magic static out of the initializing state. Without atomics, this relies on nothing else accessing the magic static at the same time, like
+/// the rest of `bare-metal` does.
+#[cfg(feature = "bare-metal")]
+#[inline]
+fn finish_initializing(initialized: &State, state: u8) {
+	unsafe { *initialized.get() = state };
+}
+
+impl Drop for PoisonOnUnwind<'_> {
+	#[inline]
+	fn drop(&mut self) {
+		finish_initializing(self.0, 3);
+	}
+}
+
+#[cfg(feature = "std")]
+#[cold]
+#[inline(never)]
+fn async_init_required(name: &'static str) -> ! {
+	panic!("The magic static `{}` has an async initializer! It must be initialized by an `async` function annotated with `#[magic_static::main]`.", name)
+}
+
+#[cold]
+#[inline(never)]
+fn context_required(name: &'static str, context: &'static str) -> ! {
+	panic!("The magic static `{}` needs a `&{}` to be initialized! Initialize it with `magic_static::init_with!` or `MagicStatic::init_with`.", name, context)
+}
+
+#[cold]
+#[inline(never)]
+fn already_initialized(name: &'static str) -> ! {
+	panic!("The magic static `{}` has already been initialized! It is initialized in strict mode by `#[magic_static::main(strict; ...)]`, which expects to be the first to initialize it.", name)
+}
+
+#[cold]
+#[inline(never)]
+fn init_failed(name: &'static str) -> ! {
+	panic!("The initializer of the magic static `{}` failed! Use `magic_static::init_try!` to handle the error.", name)
+}
+
+#[cold]
+#[inline(never)]
+fn not_initialized(name: &'static str) -> ! {
+	panic!("The magic static `{}` has not been initialized yet! You need to add it to `#[magic_static::main]` on your main function, or initialize it with `magic_static::init!` at an appropriate time.", name)
+}
+
+#[cold]
+#[inline(never)]
+#[cfg(feature = "bare-metal")]
+fn initialized_recursively(name: &'static str) -> ! {
+	panic!("The magic static `{}` was initialized again during its own initialization (by its initializer or an interrupt handler)! Make sure it doesn't depend on itself, and use the `critical-section` feature flag if interrupt handlers use it.", name)
+}
+
+#[cold]
+#[inline(never)]
+fn poisoned(name: &'static str) -> ! {
+	panic!("The magic static `{}` is poisoned: its initializer panicked", name)
+}
+
+impl<T, E> core::ops::Deref for MagicStatic<T, E> {
+	type Target = T;
+
+	#[cfg_attr(debug_assertions, inline)]
+	#[cfg_attr(not(debug_assertions), inline(always))]
+	fn deref(&self) -> &Self::Target {
+		self.debug_assert_initialized();
+		unsafe { &*(&*self.value.get()).as_ptr() }
+	}
+}
+
+#[cfg(feature = "unsafe-mut")]
+/// **This is only sound in single-threaded contexts!**
+///
+/// `MagicStatic` is unconditionally `Sync`, so nothing stops another thread from reading the value while it is being mutated through this.
+impl<T, E> core::ops::DerefMut for MagicStatic<T, E> {
+	#[cfg_attr(debug_assertions, inline)]
+	#[cfg_attr(not(debug_assertions), inline(always))]
+	fn deref_mut(&mut self) -> &mut Self::Target {
+		self.debug_assert_initialized();
+		unsafe { &mut *(&mut *self.value.get()).as_mut_ptr() }
+	}
+}
+
+impl<T, E> AsRef<T> for MagicStatic<T, E> {
+	#[inline]
+	fn as_ref(&self) -> &T {
+		self
+	}
+}
+
+impl<T, E> core::borrow::Borrow<T> for MagicStatic<T, E> {
+	#[inline]
+	fn borrow(&self) -> &T {
+		self
+	}
+}
+
+/// Iterates over the value of a magic static, so that `for x in &MAGIC` works like `for x in &*MAGIC`.
+impl<'a, T, E> IntoIterator for &'a MagicStatic<T, E>
+where
+	&'a T: IntoIterator,
+{
+	type Item = <&'a T as IntoIterator>::Item;
+	type IntoIter = <&'a T as IntoIterator>::IntoIter;
+
+	#[inline]
+	fn into_iter(self) -> Self::IntoIter {
+		(**self).into_iter()
+	}
+}
+
+#[cfg(not(feature = "single-thread"))]
+unsafe impl<T, E> Sync for MagicStatic<T, E> {}
+
+/// With the `single-thread` feature, a magic static is only `Sync` if its value is:
+///
+/// ```rust,compile_fail,E0277
+/// fn assert_sync<T: Sync>() {}
+/// assert_sync::<magic_static::MagicStatic<core::cell::Cell<u32>>>();
+/// ```
+#[cfg(feature = "single-thread")]
+unsafe impl<T: Sync, E> Sync for MagicStatic<T, E> {}
+
+#[cfg(feature = "serde")]
+impl<T: serde::Serialize, E> serde::Serialize for MagicStatic<T, E> {
+	#[inline]
+	fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+		(**self).serialize(serializer)
+	}
+}
+
+/// Deserializes a magic static which has already been initialized, for magic statics that aren't `static`s (see [`MagicStatic::init_local`].)
+///
+/// # Example
+///
+/// ```rust
+/// use magic_static::MagicStatic;
+/// use serde::{Deserialize, de::{IntoDeserializer, value::Error}};
+///
+/// let magic = MagicStatic::<u32>::deserialize(IntoDeserializer::<Error>::into_deserializer(42u32)).unwrap();
+/// assert!(magic.is_initialized());
+/// assert_eq!(*magic, 42);
+/// ```
+#[cfg(feature = "serde")]
+impl<'de, T: serde::Deserialize<'de>, E> serde::Deserialize<'de> for MagicStatic<T, E> {
+	#[inline]
+	fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+		T::deserialize(deserializer).map(Self::with_value)
+	}
+}
+
+// `static`s are never dropped, but magic statics that aren't `static`s (see `MagicStatic::init_local`) are
+impl<T, E> Drop for MagicStatic<T, E> {
+	fn drop(&mut self) {
+		if self.is_initialized() {
+			unsafe { core::ptr::drop_in_place(self.value.get_mut().as_mut_ptr()) };
+		}
+	}
+}
+
+/// A magic static whose value is a constant, declared with `const ref` in `magic_statics!`.
+///
+/// It is always initialized, so accessing its value is a plain read, and initializing it does nothing. It can be initialized (and deinitialized)
+/// alongside other magic statics all the same.
+pub struct ConstMagicStatic<T>(T);
+
+impl<T> ConstMagicStatic<T> {
+	/// Creates a new magic static with the constant `value`.
+	#[inline]
+	pub const fn new(value: T) -> Self {
+		Self(value)
+	}
+
+	/// Always returns `true`.
+	#[inline]
+	pub const fn is_initialized(&self) -> bool {
+		true
+	}
+
+	/// Returns a reference to the value of this magic static.
+	#[inline]
+	pub const fn get(&'static self) -> Option<&'static T> {
+		Some(&self.0)
+	}
+
+	/// Returns a reference to the value of this magic static with the full `'static` lifetime, see [`MagicStatic::get_static`].
+	#[inline]
+	pub const fn get_static(&'static self) -> &'static T {
+		&self.0
+	}
+
+	/// Returns a reference to part of the value of this magic static with the full `'static` lifetime, see [`MagicStatic::project`].
+	#[inline]
+	pub fn project<U: ?Sized>(&'static self, f: impl FnOnce(&'static T) -> &'static U) -> &'static U {
+		f(&self.0)
+	}
+
+	#[doc(hidden)]
+	#[inline]
+	pub fn __init(&'static self) -> bool {
+		false
+	}
+
+	#[doc(hidden)]
+	#[inline]
+	pub fn __init_strict(&'static self) {}
+
+	#[doc(hidden)]
+	pub async fn __init_async(&'static self) -> bool {
+		false
+	}
+
+	#[doc(hidden)]
+	pub async fn __init_async_strict(&'static self) {}
+
+	#[doc(hidden)]
+	#[inline]
+	/// # Safety
+	///
+	/// Always safe, for parity with [`MagicStatic`].
+	pub unsafe fn __deinit(&'static self) {}
+}
+
+impl<T> core::ops::Deref for ConstMagicStatic<T> {
+	type Target = T;
+
+	#[inline(always)]
+	fn deref(&self) -> &Self::Target {
+		&self.0
+	}
+}
+
+impl<T> AsRef<T> for ConstMagicStatic<T> {
+	#[inline]
+	fn as_ref(&self) -> &T {
+		&self.0
+	}
+}
+
+impl<T> core::borrow::Borrow<T> for ConstMagicStatic<T> {
+	#[inline]
+	fn borrow(&self) -> &T {
+		&self.0
+	}
+}
+
+/// A magic static which is initialized when its value is first accessed if it hasn't been initialized yet, declared with `lazy static ref` in `magic_statics!`
+/// or `#[magic_static(lazy)]`.
+///
+/// This is for magic statics which can't be initialized in `main` (e.g. because they are only used conditionally.) Unlike other magic statics,
+/// **every access checks whether it has been initialized**, like a lazily initialized static. It can still be initialized eagerly like any other magic static.
+///
+/// Like `std::sync::Once`, it can be accessed from multiple threads concurrently before it has been initialized: exactly one of them runs the initializer,
+/// and the others wait for it (parking with the `std` feature, spinning without it), so it's a drop-in for `lazy_static!` or `once_cell::sync::Lazy`.
+/// Once it has been initialized, every access only pays for the check.
+///
+/// The magic statics of a lazy group (`magic_statics_mod!(lazy { ... })`) are also lazy magic statics: the first access to any of them initializes the whole
+/// group.
+///
+/// Like those of other magic statics, its initializer isn't in an `unsafe` context, so calling an `unsafe` function from it needs an `unsafe` block:
+///
+/// ```compile_fail,E0133
+/// unsafe fn answer() -> u32 {
+///     42
+/// }
+///
+/// magic_static::magic_statics! {
+///     lazy static ref ANSWER: u32 = answer();
+/// }
+/// # fn main() {}
+/// ```
+///
+/// ```compile_fail,E0133
+/// # unsafe fn answer() -> u32 {
+/// #     42
+/// # }
+/// #[magic_static::magic_static(lazy)]
+/// static ANSWER: u32 = answer();
+/// # fn main() {}
+/// ```
+///
+/// ```compile_fail,E0133
+/// # unsafe fn answer() -> u32 {
+/// #     42
+/// # }
+/// mod answers {
+///     magic_static::magic_statics_mod!(lazy {
+///         pub static ref ANSWER: u32 = super::answer();
+///     });
+/// }
+/// # fn main() {}
+/// ```
+pub struct LazyMagicStatic<T>(MagicStatic<T>, Option<&'static MagicStatic<()>>);
+
+impl<T> LazyMagicStatic<T> {
+	#[doc(hidden)]
+	#[inline]
+	/// # Safety
+	///
+	/// The lazy magic static must be a `static`, as accessing its value initializes it through a `'static` reference.
+	pub const unsafe fn __new(magic_static: MagicStatic<T>) -> Self {
+		Self(magic_static, None)
+	}
+
+	#[doc(hidden)]
+	#[inline]
+	/// # Safety
+	///
+	/// See [`LazyMagicStatic::__new`]. `group` initializes the lazy group this magic static belongs to.
+	pub const unsafe fn __new_in_group(magic_static: MagicStatic<T>, group: &'static MagicStatic<()>) -> Self {
+		Self(magic_static, Some(group))
+	}
+
+	/// Returns whether this magic static has been initialized and its value is ready to be accessed.
+	#[inline]
+	pub fn is_initialized(&self) -> bool {
+		self.0.is_initialized()
+	}
+
+	/// Returns the name of this magic static, see [`MagicStatic::name`].
+	#[inline]
+	pub const fn name(&self) -> &'static str {
+		self.0.name()
+	}
+
+	/// Returns the state of this magic static, see [`MagicStatic::state`].
+	#[inline]
+	pub fn state(&self) -> InitState {
+		self.0.state()
+	}
+
+	/// Returns the raw state of this magic static, see [`MagicStatic::raw_state`].
+	#[inline]
+	pub fn raw_state(&self) -> u8 {
+		self.0.raw_state()
+	}
+
+	/// Returns a reference to the value of this magic static, or `None` if it hasn't been initialized yet, without initializing it.
+	#[inline]
+	pub fn get(&'static self) -> Option<&'static T> {
+		self.0.get()
+	}
+
+	/// Returns a reference to the value of this magic static without initializing it, or `Err(Poisoned)` if its initializer panicked.
+	/// See [`MagicStatic::try_get`].
+	#[inline]
+	pub fn try_get(&'static self) -> Result<Option<&'static T>, Poisoned> {
+		self.0.try_get()
+	}
+
+	/// Returns a reference to the value of this magic static with the full `'static` lifetime, initializing it first if it hasn't been initialized yet.
+	/// See [`MagicStatic::get_static`].
+	#[inline]
+	pub fn get_static(&'static self) -> &'static T
+	where
+		MagicStatic<T>: Sync,
+	{
+		if let (false, Some(group)) = (self.0.is_initialized(), self.1) {
+			// Without waiting, so that accessing a magic static of the group while it is being initialized (on this thread or another one) initializes
+			// just that magic static, instead of waiting for the group forever
+			group.poll_init();
+		}
+		self.0.get_or_init()
+	}
+
+	/// Returns a reference to part of the value of this magic static with the full `'static` lifetime, initializing it first if it hasn't been
+	/// initialized yet. See [`MagicStatic::project`].
+	#[inline]
+	pub fn project<U: ?Sized>(&'static self, f: impl FnOnce(&'static T) -> &'static U) -> &'static U
+	where
+		MagicStatic<T>: Sync,
+	{
+		f(self.get_static())
+	}
+
+	/// Returns a clone of the value of this magic static, initializing it first if it hasn't been initialized yet. See [`MagicStatic::clone_inner`].
+	#[inline]
+	pub fn clone_inner(&'static self) -> T
+	where
+		T: Clone,
+		MagicStatic<T>: Sync,
+	{
+		self.get_static().clone()
+	}
+
+	/// Parses the value of this magic static, initializing it first if it hasn't been initialized yet. See [`MagicStatic::parse`].
+	#[inline]
+	pub fn parse<U: core::str::FromStr>(&'static self) -> Result<U, U::Err>
+	where
+		T: AsRef<str>,
+		MagicStatic<T>: Sync,
+	{
+		self.get_static().as_ref().parse()
+	}
+
+	#[doc(hidden)]
+	#[inline]
+	pub fn __init(&'static self) -> bool
+	where
+		MagicStatic<T>: Sync,
+	{
+		self.0.__init()
+	}
+
+	#[doc(hidden)]
+	#[inline]
+	pub fn __init_strict(&'static self)
+	where
+		MagicStatic<T>: Sync,
+	{
+		self.0.__init_strict()
+	}
+
+	#[doc(hidden)]
+	pub async fn __init_async(&'static self) -> bool
+	where
+		MagicStatic<T>: Sync,
+	{
+		self.0.__init_async().await
+	}
+
+	#[doc(hidden)]
+	pub async fn __init_async_strict(&'static self)
+	where
+		MagicStatic<T>: Sync,
+	{
+		self.0.__init_async_strict().await
+	}
+
+	#[doc(hidden)]
+	#[cfg(feature = "startup")]
+	#[inline]
+	pub fn __init_startup(&'static self)
+	where
+		MagicStatic<T>: Sync,
+	{
+		self.0.__init_startup()
+	}
+
+	#[doc(hidden)]
+	#[inline]
+	/// # Safety
+	///
+	/// See [`MagicStatic::reset`].
+	pub unsafe fn __deinit(&'static self)
+	where
+		MagicStatic<T>: Sync,
+	{
+		self.0.reset()
+	}
+}
+
+impl<T: 'static> core::ops::Deref for LazyMagicStatic<T>
+where
+	MagicStatic<T>: Sync,
+{
+	type Target = T;
+
+	#[inline]
+	fn deref(&self) -> &Self::Target {
+		// SAFETY: Lazy magic statics are always `static`s
+		unsafe { &*(self as *const Self) }.get_static()
+	}
+}
+
+impl<T: 'static> AsRef<T> for LazyMagicStatic<T>
+where
+	MagicStatic<T>: Sync,
+{
+	#[inline]
+	fn as_ref(&self) -> &T {
+		self
+	}
+}
+
+impl<T: 'static> core::borrow::Borrow<T> for LazyMagicStatic<T>
+where
+	MagicStatic<T>: Sync,
+{
+	#[inline]
+	fn borrow(&self) -> &T {
+		self
+	}
+}
+
+impl<U> LazyMagicStatic<Option<U>> {
+	/// Returns a reference to the value inside the `Option` held by this magic static, initializing it first if it hasn't been initialized yet.
+	/// See [`MagicStatic::as_option`].
+	#[inline]
+	pub fn as_option(&'static self) -> Option<&'static U>
+	where
+		MagicStatic<Option<U>>: Sync,
+	{
+		self.get_static().as_ref()
+	}
+}
+
+impl<T> AnyMagicStatic for LazyMagicStatic<T>
+where
+	MagicStatic<T>: Sync,
+{
+	#[inline]
+	fn __init_any(&'static self) {
+		self.0.__init_any()
+	}
+
+	#[inline]
+	fn __name(&self) -> &'static str {
+		self.0.__name()
+	}
+
+	#[inline]
+	fn __is_initialized_any(&self) -> bool {
+		self.0.__is_initialized_any()
+	}
+
+	#[inline]
+	unsafe fn __deinit_any(&'static self) {
+		self.0.__deinit_any()
+	}
+
+	#[inline]
+	fn __depends_on(&self) -> &'static [&'static dyn AnyMagicStatic] {
+		AnyMagicStatic::__depends_on(&self.0)
+	}
+
+	#[inline]
+	fn __is_lazy(&self) -> bool {
+		true
+	}
 }
 
-#[macro_export]
-#[doc(hidden)]
-#[cfg(feature = "bare-metal")]
-macro_rules! __magic_static_initialized {
-	() => {
-		::core::cell::UnsafeCell::new(false)
-	};
-}
+/// A magic static whose accesses always (`CHECK = true`) or never (`CHECK = false`) check whether it has been initialized, whatever the build profile,
+/// declared with `#[check = always]`/`#[check = never]` in `magic_statics!` or `#[magic_static(check = always)]`/`#[magic_static(check = never)]`.
+///
+/// This is for the odd magic static which is either accessed from code that can't be trusted to initialize it (so the check is worth keeping in release
+/// builds), or accessed in a hot loop of a debug build (so the check is worth dropping.) Otherwise, it behaves exactly like a [`MagicStatic`].
+#[repr(transparent)]
+pub struct CheckedMagicStatic<T, const CHECK: bool, E = core::convert::Infallible>(MagicStatic<T, E>);
+
+impl<T, const CHECK: bool, E> CheckedMagicStatic<T, CHECK, E> {
+	#[doc(hidden)]
+	#[inline]
+	pub const fn __new(magic_static: MagicStatic<T, E>) -> Self {
+		Self(magic_static)
+	}
+
+	/// Returns whether this magic static has been initialized and its value is ready to be accessed.
+	#[inline]
+	pub fn is_initialized(&self) -> bool {
+		self.0.is_initialized()
+	}
+
+	/// Returns the name of this magic static, see [`MagicStatic::name`].
+	#[inline]
+	pub const fn name(&self) -> &'static str {
+		self.0.name()
+	}
+
+	/// Returns the state of this magic static, see [`MagicStatic::state`].
+	#[inline]
+	pub fn state(&self) -> InitState {
+		self.0.state()
+	}
+
+	/// Returns the raw state of this magic static, see [`MagicStatic::raw_state`].
+	#[inline]
+	pub fn raw_state(&self) -> u8 {
+		self.0.raw_state()
+	}
+
+	/// Returns a reference to the value of this magic static, or `None` if it hasn't been initialized yet.
+	#[inline]
+	pub fn get(&'static self) -> Option<&'static T> {
+		self.0.get()
+	}
+
+	/// Returns a reference to the value of this magic static, `Ok(None)` if it hasn't been initialized yet, or `Err(Poisoned)` if its initializer
+	/// panicked. See [`MagicStatic::try_get`].
+	#[inline]
+	pub fn try_get(&'static self) -> Result<Option<&'static T>, Poisoned> {
+		self.0.try_get()
+	}
+
+	/// Returns a reference to the value of this magic static with the full `'static` lifetime, checking that it has been initialized the same way
+	/// dereferencing it does.
+	#[inline]
+	pub fn get_static(&'static self) -> &'static T {
+		self.value()
+	}
+
+	/// Blocks the current thread until this magic static has been initialized by another thread, see [`MagicStatic::wait_initialized`].
+	#[cfg(all(feature = "std", not(feature = "bare-metal")))]
+	#[inline]
+	pub fn wait_initialized(&'static self) {
+		self.0.wait_initialized()
+	}
+
+	/// Returns a reference to part of the value of this magic static with the full `'static` lifetime. See [`MagicStatic::project`].
+	#[inline]
+	pub fn project<U: ?Sized>(&'static self, f: impl FnOnce(&'static T) -> &'static U) -> &'static U {
+		f(self.get_static())
+	}
+
+	/// Returns a clone of the value of this magic static, see [`MagicStatic::clone_inner`].
+	#[inline]
+	pub fn clone_inner(&'static self) -> T
+	where
+		T: Clone,
+	{
+		self.get_static().clone()
+	}
+
+	/// Parses the value of this magic static, see [`MagicStatic::parse`].
+	#[inline]
+	pub fn parse<U: core::str::FromStr>(&'static self) -> Result<U, U::Err>
+	where
+		T: AsRef<str>,
+	{
+		self.get_static().as_ref().parse()
+	}
+
+	#[inline(always)]
+	fn value(&self) -> &T {
+		if CHECK {
+			self.0.assert_initialized();
+		}
+		unsafe { &*(&*self.0.value.get()).as_ptr() }
+	}
 
-#[doc(hidden)]
-pub struct MagicStatic<T> {
 	#[doc(hidden)]
-	#[cfg(not(feature = "bare-metal"))]
-	pub initialized: core::sync::atomic::AtomicU8,
+	#[inline]
+	pub fn __init(&'static self) -> bool
+	where
+		MagicStatic<T, E>: Sync,
+	{
+		self.0.__init()
+	}
 
 	#[doc(hidden)]
-	#[cfg(feature = "bare-metal")]
-	pub initialized: UnsafeCell<bool>,
+	#[inline]
+	pub fn __init_strict(&'static self)
+	where
+		MagicStatic<T, E>: Sync,
+	{
+		self.0.__init_strict()
+	}
 
 	#[doc(hidden)]
-	pub value: UnsafeCell<MaybeUninit<T>>,
+	pub async fn __init_async(&'static self) -> bool
+	where
+		MagicStatic<T, E>: Sync,
+	{
+		self.0.__init_async().await
+	}
+
+	#[doc(hidden)]
+	pub async fn __init_async_strict(&'static self)
+	where
+		MagicStatic<T, E>: Sync,
+	{
+		self.0.__init_async_strict().await
+	}
+
+	#[doc(hidden)]
+	#[cfg(feature = "startup")]
+	#[inline]
+	pub fn __init_startup(&'static self)
+	where
+		MagicStatic<T, E>: Sync,
+	{
+		self.0.__init_startup()
+	}
 
 	#[doc(hidden)]
-	pub init: fn() -> T,
+	#[inline]
+	/// # Safety
+	///
+	/// See [`MagicStatic::reset`].
+	pub unsafe fn __deinit(&'static self)
+	where
+		MagicStatic<T, E>: Sync,
+	{
+		self.0.reset()
+	}
 }
-impl<T> MagicStatic<T> {
+
+impl<T, const CHECK: bool, E> core::ops::Deref for CheckedMagicStatic<T, CHECK, E> {
+	type Target = T;
+
+	#[inline(always)]
+	fn deref(&self) -> &Self::Target {
+		self.value()
+	}
+}
+
+impl<T, const CHECK: bool, E> AsRef<T> for CheckedMagicStatic<T, CHECK, E> {
 	#[inline]
-	#[cfg(not(feature = "bare-metal"))]
-	fn initialized(&self) -> bool {
-		self.initialized.load(core::sync::atomic::Ordering::Acquire) == 2
+	fn as_ref(&self) -> &T {
+		self
 	}
+}
 
+impl<T, const CHECK: bool, E> core::borrow::Borrow<T> for CheckedMagicStatic<T, CHECK, E> {
 	#[inline]
-	#[cfg(feature = "bare-metal")]
-	fn initialized(&self) -> bool {
-		unsafe { *self.initialized.get() }
+	fn borrow(&self) -> &T {
+		self
 	}
+}
 
-	#[doc(hidden)]
+impl<U, const CHECK: bool, E> CheckedMagicStatic<Option<U>, CHECK, E> {
+	/// Returns a reference to the value inside the `Option` held by this magic static, see [`MagicStatic::as_option`].
 	#[inline]
-	pub fn __init(&'static self) {
-		unsafe {
-			#[cfg(not(feature = "bare-metal"))]
-			match self.initialized.compare_exchange(0, 1, core::sync::atomic::Ordering::SeqCst, core::sync::atomic::Ordering::SeqCst) {
-				Ok(0) => {
-					(&mut *self.value.get()).as_mut_ptr().write((self.init)());
-					self.initialized.store(2, core::sync::atomic::Ordering::SeqCst);
-				},
+	pub fn as_option(&'static self) -> Option<&'static U> {
+		self.get_static().as_ref()
+	}
+}
 
-				Err(0) | Err(1) => {
-					// Spin and wait
-					while self.initialized.load(core::sync::atomic::Ordering::Relaxed) != 2 {
-						core::hint::spin_loop();
-					}
-				},
+impl<T, const CHECK: bool, E> AnyMagicStatic for CheckedMagicStatic<T, CHECK, E>
+where
+	MagicStatic<T, E>: Sync,
+{
+	#[inline]
+	fn __init_any(&'static self) {
+		self.0.__init_any()
+	}
 
-				Err(2) => {},
+	#[inline]
+	fn __name(&self) -> &'static str {
+		self.0.__name()
+	}
 
-				code => unreachable!("{:?}", code)
-			}
+	#[inline]
+	fn __is_initialized_any(&self) -> bool {
+		self.0.__is_initialized_any()
+	}
 
-			#[cfg(feature = "bare-metal")]
-			if !*self.initialized.get() {
-				*self.initialized.get() = true;
-				(&mut *self.value.get()).as_mut_ptr().write((self.init)());
-			}
-		}
+	#[inline]
+	unsafe fn __deinit_any(&'static self) {
+		self.0.__deinit_any()
+	}
+
+	#[inline]
+	fn __depends_on(&self) -> &'static [&'static dyn AnyMagicStatic] {
+		AnyMagicStatic::__depends_on(&self.0)
 	}
 }
-impl<T> core::ops::Deref for MagicStatic<T> {
-	type Target = T;
 
-	#[cfg_attr(debug_assertions, inline)]
-	#[cfg_attr(not(debug_assertions), inline(always))]
-	fn deref(&self) -> &Self::Target {
-		debug_assert!(
-			self.initialized(),
-			"This magic static has not been initialized yet! You need to add `#[magic_static::main]` to your main function, or call `magic_static::init()` at an appropriate time."
-		);
-		unsafe { &*(&*self.value.get()).as_ptr() }
+/// The error returned by [`MagicStatic::try_init`] when the magic static has already been initialized.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct AlreadyInitialized;
+impl core::fmt::Display for AlreadyInitialized {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		f.write_str("magic static has already been initialized")
 	}
 }
 
-unsafe impl<T> Sync for MagicStatic<T> {}
+/// The error returned by [`MagicStatic::try_get`] when the initializer of the magic static panicked.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Poisoned;
+impl core::fmt::Display for Poisoned {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		f.write_str("magic static is poisoned: its initializer panicked")
+	}
+}
+
+/// The state of a magic static, returned by [`MagicStatic::state`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum InitState {
+	/// The magic static hasn't been initialized yet (or has been reset.)
+	Uninitialized,
+	/// The initializer of the magic static is running.
+	Initializing,
+	/// The magic static has been initialized and its value is ready to be accessed.
+	Initialized,
+	/// The initializer of the magic static panicked.
+	Poisoned,
+}
 
+/// Forwards the formatting traits of a magic static's value to the magic static, given its generics (which must name its value's type `T`) and type.
 macro_rules! impl_fmt {
-	{ $($fmt:path),+ } => {
+	{ @impl [$($generics:tt)*] $ty:ty: $fmt:path } => {
+		impl<$($generics)*> $fmt for $ty
+		where
+			T: $fmt,
+			$ty: core::ops::Deref<Target = T>,
+		{
+			fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+				(**self).fmt(f)
+			}
+		}
+	};
+
+	{ $generics:tt $ty:ty: $($fmt:path),+ } => {
+		$(impl_fmt! { @impl $generics $ty: $fmt })+
+	};
+
+	{ $($generics:tt $ty:ty),+ } => {
 		$(
-			impl<T: $fmt> $fmt for MagicStatic<T> {
-				fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
-					(**self).fmt(f)
-				}
+			impl_fmt! {
+				$generics $ty:
+				core::fmt::Debug,
+				core::fmt::Display,
+				core::fmt::Binary,
+				core::fmt::LowerHex,
+				core::fmt::UpperHex,
+				core::fmt::Octal,
+				core::fmt::Pointer,
+				core::fmt::LowerExp,
+				core::fmt::UpperExp
 			}
 		)+
 	};
 }
 impl_fmt! {
-	core::fmt::Debug,
-	core::fmt::Display,
-	core::fmt::Binary,
-	core::fmt::LowerHex,
-	core::fmt::UpperHex,
-	core::fmt::Octal,
-	core::fmt::Pointer,
-	core::fmt::LowerExp,
-	core::fmt::UpperExp
+	[T, E] MagicStatic<T, E>,
+	[T] ConstMagicStatic<T>,
+	[T: 'static] LazyMagicStatic<T>,
+	[T, const CHECK: bool, E] CheckedMagicStatic<T, CHECK, E>
+}
+
+/// Forwards the comparison and hashing traits of a magic static's value to the magic static, like `impl_fmt!`.
+macro_rules! impl_cmp {
+	{ $([$($generics:tt)*] $ty:ty),+ } => {
+		$(
+			impl<$($generics)*> PartialEq for $ty
+			where
+				T: PartialEq,
+				$ty: core::ops::Deref<Target = T>,
+			{
+				#[inline]
+				fn eq(&self, other: &Self) -> bool {
+					**self == **other
+				}
+			}
+			impl<$($generics)*> Eq for $ty where T: Eq, $ty: core::ops::Deref<Target = T> {}
+			impl<$($generics)*> core::hash::Hash for $ty
+			where
+				T: core::hash::Hash,
+				$ty: core::ops::Deref<Target = T>,
+			{
+				#[inline]
+				fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+					(**self).hash(state)
+				}
+			}
+			impl<$($generics)*> PartialOrd for $ty
+			where
+				T: PartialOrd,
+				$ty: core::ops::Deref<Target = T>,
+			{
+				#[inline]
+				fn partial_cmp(&self, other: &Self) -> Option<core::cmp::Ordering> {
+					(**self).partial_cmp(&**other)
+				}
+			}
+			impl<$($generics)*> Ord for $ty
+			where
+				T: Ord,
+				$ty: core::ops::Deref<Target = T>,
+			{
+				#[inline]
+				fn cmp(&self, other: &Self) -> core::cmp::Ordering {
+					(**self).cmp(&**other)
+				}
+			}
+		)+
+	};
+}
+impl_cmp! {
+	[T, E] MagicStatic<T, E>,
+	[T] ConstMagicStatic<T>,
+	[T: 'static] LazyMagicStatic<T>,
+	[T, const CHECK: bool, E] CheckedMagicStatic<T, CHECK, E>
+}
+
+
+#[macro_export]
+#[doc(hidden)]
+#[cfg(not(feature = "unsafe-mut"))]
+macro_rules! __magic_static_mut {
+	() => {
+		compile_error!("`#[magic_static]` on a `static mut` requires the `unsafe-mut` feature");
+	};
+}
+
+#[macro_export]
+#[doc(hidden)]
+#[cfg(feature = "unsafe-mut")]
+macro_rules! __magic_static_mut {
+	() => {};
+}
+
+#[macro_export]
+#[doc(hidden)]
+#[cfg(not(feature = "auto-register"))]
+macro_rules! __magic_static_register {
+	(group = $group:literal, $path:path) => {
+		compile_error!("Grouping magic statics requires the `auto-register` feature");
+	};
+
+	($path:path) => {};
+}
+
+#[macro_export]
+#[doc(hidden)]
+#[cfg(all(feature = "auto-register", not(feature = "startup")))]
+macro_rules! __magic_static_register {
+	(group = $group:literal, $path:path) => {
+		// Registering a `#[deprecated]` magic static isn't a use of it
+		#[allow(deprecated)]
+		const _: () = {
+			$crate::private::inventory::submit! {
+				$crate::private::Registration(&$path, ::core::option::Option::Some($group))
+			}
+		};
+	};
+
+	($path:path) => {
+		#[allow(deprecated)]
+		const _: () = {
+			$crate::private::inventory::submit! {
+				$crate::private::Registration(&$path, ::core::option::Option::None)
+			}
+		};
+	};
+}
+
+#[macro_export]
+#[doc(hidden)]
+#[cfg(feature = "startup")]
+macro_rules! __magic_static_register {
+	(@startup $path:path) => {
+		#[allow(deprecated)]
+		const _: () = {
+			#[$crate::private::ctor::ctor]
+			fn magic_static_startup() {
+				$path.__init_startup();
+			}
+		};
+	};
+
+	(group = $group:literal, $path:path) => {
+		// Registering a `#[deprecated]` magic static isn't a use of it
+		#[allow(deprecated)]
+		const _: () = {
+			$crate::private::inventory::submit! {
+				$crate::private::Registration(&$path, ::core::option::Option::Some($group))
+			}
+		};
+
+		$crate::__magic_static_register!(@startup $path);
+	};
+
+	($path:path) => {
+		#[allow(deprecated)]
+		const _: () = {
+			$crate::private::inventory::submit! {
+				$crate::private::Registration(&$path, ::core::option::Option::None)
+			}
+		};
+
+		$crate::__magic_static_register!(@startup $path);
+	};
+}
+
+#[macro_export]
+#[doc(hidden)]
+#[cfg(not(feature = "auto-register"))]
+macro_rules! __magic_static_init_before {
+	($before:path, $after:path) => {
+		compile_error!("Ordering magic statics with `before` and `after` requires the `auto-register` feature");
+	};
+}
+
+#[macro_export]
+#[doc(hidden)]
+#[cfg(feature = "auto-register")]
+macro_rules! __magic_static_init_before {
+	($before:path, $after:path) => {
+		#[allow(deprecated)]
+		const _: () = {
+			$crate::private::inventory::submit! {
+				$crate::private::InitBefore(&$before, &$after)
+			}
+		};
+	};
+}
+
+#[macro_export]
+#[doc(hidden)]
+#[cfg(not(feature = "verify-init"))]
+macro_rules! __magic_static_verify_init {
+	() => {};
+}
+
+#[macro_export]
+#[doc(hidden)]
+#[cfg(feature = "verify-init")]
+macro_rules! __magic_static_verify_init {
+	() => {
+		$crate::private::__verify_init();
+	};
+}
+
+#[cfg(feature = "std")]
+pub use std::boxed::Box;
+
+#[cfg(feature = "std")]
+pub use crate::env::__from_env;
+
+#[doc(hidden)]
+#[cfg(feature = "parallel-init")]
+/// Runs each of the initializers of `init_parallel!` on its own thread, returning the sum of the magic statics they initialized.
+pub fn __init_parallel(inits: &[fn() -> usize]) -> usize {
+	std::thread::scope(|scope| {
+		let threads = inits.iter().map(|&init| scope.spawn(init)).collect::<std::vec::Vec<_>>();
+		threads.into_iter().map(|thread| thread.join().unwrap_or_else(|panic| std::panic::resume_unwind(panic))).sum()
+	})
+}
+
+#[cfg(feature = "std")]
+pub use std::thread_local;
+
+#[cfg(feature = "auto-register")]
+pub use inventory;
+
+#[cfg(feature = "startup")]
+pub use ctor;
+
+#[doc(hidden)]
+#[cfg(feature = "auto-register")]
+/// A registered magic static, and the group it belongs to.
+pub struct Registration(pub &'static dyn AnyMagicStatic, pub Option<&'static str>);
+
+#[cfg(feature = "auto-register")]
+inventory::collect!(Registration);
+
+#[doc(hidden)]
+#[cfg(feature = "auto-register")]
+/// An ordering constraint declared with `#[magic_static(before(...))]` or `#[magic_static(after(...))]`: `magic_static::init_all!()` initializes the
+/// first magic static before the second.
+pub struct InitBefore(pub &'static dyn AnyMagicStatic, pub &'static dyn AnyMagicStatic);
+
+#[cfg(feature = "auto-register")]
+inventory::collect!(InitBefore);
+
+#[doc(hidden)]
+#[cfg(feature = "auto-register")]
+pub fn __init_all() {
+	for registration in inventory::iter::<Registration> {
+		init_ordered(registration.0, None);
+	}
+}
+
+/// Initializes a magic static after its dependencies and the magic statics which must be initialized before it, panicking if they form a cycle.
+#[cfg(feature = "auto-register")]
+fn init_ordered(magic_static: &'static dyn AnyMagicStatic, parent: Option<&DependencyPath>) {
+	if magic_static.__is_initialized_any() {
+		return;
+	}
+
+	let path = DependencyPath::new(magic_static, parent);
+	if path.repeats() {
+		panic!("Init order cycle detected between magic statics: {}", DependencyCycle(&path));
+	}
+
+	for dependency in magic_static.__depends_on() {
+		init_ordered(*dependency, Some(&path));
+	}
+	for constraint in inventory::iter::<InitBefore> {
+		if same_magic_static(constraint.1, magic_static) {
+			init_ordered(constraint.0, Some(&path));
+		}
+	}
+
+	magic_static.__init_any();
+}
+
+#[doc(hidden)]
+#[cfg(any(feature = "std", feature = "alloc"))]
+/// # Safety
+///
+/// See [`MagicStatic::reset`].
+pub unsafe fn __deinit_all() {
+	crate::teardown::deinit_all();
+}
+
+#[doc(hidden)]
+#[cfg(feature = "auto-register")]
+pub fn __init_group(group: &str) {
+	for registration in inventory::iter::<Registration> {
+		if registration.1 == Some(group) {
+			registration.0.__init_any();
+		}
+	}
+}
+
+#[doc(hidden)]
+#[inline]
+/// Coerces an in-place initializer to a function pointer, so that `array_from_fn!` can type-check it outside of an `unsafe` block.
+pub const fn __in_place_init<T>(init: fn(&mut MaybeUninit<T>)) -> fn(&mut MaybeUninit<T>) {
+	init
+}
+
+#[doc(hidden)]
+#[inline]
+/// Initializes an array element by element, without the array passing through the stack.
+pub fn __fill_array<U, const N: usize>(array: &mut MaybeUninit<[U; N]>, mut f: impl FnMut(usize) -> U) {
+	let elements = array.as_mut_ptr() as *mut U;
+	for i in 0..N {
+		unsafe { elements.add(i).write(f(i)) };
+	}
+}
+
+/// A value stored at (at least) the alignment of `A`, which is how `#[magic_static(align = N)]` over-aligns the value of a magic static (e.g. to a cache
+/// line, for a lookup table accessed in hot loops.) It dereferences to the value, and doesn't change its size other than rounding it up to the alignment.
+///
+/// `A` is a zero-sized marker chosen by the attribute.
+#[repr(C)]
+pub struct Aligned<T, A> {
+	_align: [A; 0],
+	value: T,
+}
+impl<T, A> Aligned<T, A> {
+	/// Wraps `value` to be stored at the alignment of `A`.
+	#[inline]
+	pub const fn new(value: T) -> Self {
+		Self { _align: [], value }
+	}
+
+	/// Returns the wrapped value.
+	#[inline]
+	pub fn into_inner(self) -> T {
+		self.value
+	}
+
+	#[doc(hidden)]
+	#[inline]
+	/// Projects to the wrapped value, which is at the start of the wrapper as it has no other non-zero-sized fields.
+	pub fn __uninit_inner(aligned: &mut MaybeUninit<Self>) -> &mut MaybeUninit<T> {
+		unsafe { &mut *(aligned as *mut MaybeUninit<Self> as *mut MaybeUninit<T>) }
+	}
+}
+impl<T, A> core::ops::Deref for Aligned<T, A> {
+	type Target = T;
+
+	#[inline]
+	fn deref(&self) -> &T {
+		&self.value
+	}
+}
+impl<T, A> core::ops::DerefMut for Aligned<T, A> {
+	#[inline]
+	fn deref_mut(&mut self) -> &mut T {
+		&mut self.value
+	}
+}
+impl<T, A> AsRef<T> for Aligned<T, A> {
+	#[inline]
+	fn as_ref(&self) -> &T {
+		&self.value
+	}
+}
+impl<T: Clone, A> Clone for Aligned<T, A> {
+	#[inline]
+	fn clone(&self) -> Self {
+		Self::new(self.value.clone())
+	}
+}
+impl<T: core::fmt::Debug, A> core::fmt::Debug for Aligned<T, A> {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		self.value.fmt(f)
+	}
+}
+
+macro_rules! alignments {
+	($($align:ident = $bytes:literal),*) => {
+		$(
+			#[doc(hidden)]
+			#[repr(align($bytes))]
+			/// An alignment marker for [`Aligned`], generated by `#[magic_static(align = N)]`.
+			pub struct $align;
+		)*
+	};
+}
+alignments! {
+	Align1 = 1, Align2 = 2, Align4 = 4, Align8 = 8, Align16 = 16, Align32 = 32, Align64 = 64, Align128 = 128, Align256 = 256, Align512 = 512,
+	Align1024 = 1024, Align2048 = 2048, Align4096 = 4096, Align8192 = 8192, Align16384 = 16384, Align32768 = 32768, Align65536 = 65536
+}
+
+/// Returns the names of every magic static in the program which hasn't been initialized (yet), **in an unspecified order**.
+///
+/// This is useful to check that every magic static was initialized at some point (e.g. after startup), before accessing an uninitialized one becomes undefined behaviour in release builds.
+///
+/// Requires the `auto-register` feature flag, and the `std` or `alloc` feature flag.
+///
+/// # Example
+///
+/// ```rust
+/// # #[macro_use] extern crate magic_static;
+/// magic_statics! {
+///     static ref EARLY: usize = 1;
+///     static ref DEFERRED: usize = 2;
+/// }
+///
+/// fn main() {
+///     magic_static::init! { EARLY };
+///
+///     for name in magic_static::uninitialized_names() {
+///         println!("`{}` hasn't been initialized yet", name);
+///     }
+/// }
+/// ```
+#[cfg(all(feature = "auto-register", any(feature = "std", feature = "alloc")))]
+pub fn uninitialized_names() -> alloc::vec::Vec<&'static str> {
+	inventory::iter::<Registration>
+		.into_iter()
+		.filter(|registration| !registration.0.__is_initialized_any())
+		.map(|registration| registration.0.__name())
+		.collect()
 }
+
+/// Returns how many magic statics in the program have been initialized, and how many there are in total, as `(initialized, total)`.
+///
+/// This is cheap enough to call often, for example from a readiness probe reporting startup progress while magic statics are still being initialized.
+/// Magic statics which are initialized while this is counting may or may not be counted.
+///
+/// Requires the `auto-register` feature flag.
+///
+/// # Example
+///
+/// ```rust
+/// # #[macro_use] extern crate magic_static;
+/// magic_statics! {
+///     static ref DATABASE: &'static str = "connected";
+///     static ref CACHE: &'static str = "warm";
+/// }
+///
+/// fn readiness() -> String {
+///     let (initialized, total) = magic_static::initialized_count();
+///     format!("{}/{} magic statics initialized", initialized, total)
+/// }
+///
+/// fn main() {
+///     magic_static::init! { DATABASE, CACHE };
+///     assert_eq!(readiness(), "2/2 magic statics initialized");
+/// }
+/// ```
+#[cfg(feature = "auto-register")]
+pub fn initialized_count() -> (usize, usize) {
+	inventory::iter::<Registration>.into_iter().fold((0, 0), |(initialized, total), registration| {
+		(initialized + registration.0.__is_initialized_any() as usize, total + 1)
+	})
+}
+
+#[doc(hidden)]
+#[cfg(feature = "verify-init")]
+/// Panics if a magic static hasn't been initialized by the time `main` has initialized its magic statics, in debug builds.
+pub fn __verify_init() {
+	if !cfg!(debug_assertions) {
+		return;
+	}
+
+	let uninitialized = inventory::iter::<Registration>
+		.into_iter()
+		.filter(|registration| !registration.0.__is_initialized_any() && !registration.0.__is_lazy())
+		.map(|registration| registration.0.__name())
+		.collect::<std::vec::Vec<_>>();
+
+	if !uninitialized.is_empty() {
+		panic!("These magic statics haven't been initialized by `#[magic_static::main]`: {}", uninitialized.join(", "));
+	}
+}
\ No newline at end of file
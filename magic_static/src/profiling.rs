@@ -0,0 +1,40 @@
+use std::{sync::Mutex, time::{Duration, Instant}, vec::Vec};
+
+static INIT_TIMINGS: Mutex<Vec<(&'static str, Duration)>> = Mutex::new(Vec::new());
+
+pub(crate) fn timed<T>(name: &'static str, init: impl FnOnce() -> T) -> T {
+	let start = Instant::now();
+	let value = init();
+	record(name, start.elapsed());
+	value
+}
+
+pub(crate) fn record(name: &'static str, elapsed: Duration) {
+	INIT_TIMINGS.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).push((name, elapsed));
+}
+
+/// Returns the name of every magic static initialized so far and how long its initializer took to run, **in the order they were initialized**.
+///
+/// Requires the `profiling` feature flag.
+///
+/// # Example
+///
+/// ```rust
+/// # #[macro_use] extern crate magic_static;
+/// magic_statics! {
+///     static ref SLOW: usize = {
+///         std::thread::sleep(std::time::Duration::from_millis(10));
+///         42
+///     };
+/// }
+///
+/// #[magic_static::main(SLOW)]
+/// fn main() {
+///     for (name, elapsed) in magic_static::init_timings() {
+///         println!("{} took {:?} to initialize", name, elapsed);
+///     }
+/// }
+/// ```
+pub fn init_timings() -> Vec<(&'static str, Duration)> {
+	INIT_TIMINGS.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).clone()
+}
\ No newline at end of file
@@ -0,0 +1,40 @@
+use std::{sync::OnceLock, time::Duration};
+
+/// The default maximum amount of time a thread will wait for another thread to finish initializing a magic static before panicking.
+///
+/// This can be overridden with the `MAGIC_STATIC_SPIN_TIMEOUT` environment variable (in seconds), which is read once, the first time a thread has to wait.
+///
+/// Requires the `spin-timeout` feature flag.
+pub const SPIN_TIMEOUT: Duration = Duration::from_secs(30);
+
+#[cfg_attr(feature = "bare-metal", allow(dead_code))]
+fn spin_timeout() -> Duration {
+	static TIMEOUT: OnceLock<Duration> = OnceLock::new();
+	*TIMEOUT.get_or_init(|| {
+		std::env::var("MAGIC_STATIC_SPIN_TIMEOUT")
+			.ok()
+			.and_then(|secs| Duration::try_from_secs_f64(secs.trim().parse().ok()?).ok())
+			.unwrap_or(SPIN_TIMEOUT)
+	})
+}
+
+#[cfg(not(feature = "bare-metal"))]
+pub(crate) struct SpinDeadline(std::time::Instant);
+
+#[cfg(not(feature = "bare-metal"))]
+impl SpinDeadline {
+	#[inline]
+	pub(crate) fn start() -> Self {
+		Self(std::time::Instant::now())
+	}
+
+	/// Returns how much longer to wait for, panicking if the timeout has elapsed.
+	#[inline]
+	pub(crate) fn remaining(&self, name: &'static str) -> Duration {
+		let timeout = spin_timeout();
+		match timeout.checked_sub(self.0.elapsed()) {
+			Some(remaining) if !remaining.is_zero() => remaining,
+			_ => panic!("The initialization of the magic static `{}` did not complete within {:?}! The thread initializing it may have stalled or deadlocked.", name, timeout),
+		}
+	}
+}
\ No newline at end of file
@@ -0,0 +1,59 @@
+use crate::private::AnyMagicStatic;
+use alloc::vec::Vec;
+
+/// Every initialized magic static, in the order they were initialized.
+#[cfg(feature = "std")]
+static INITIALIZED: std::sync::Mutex<Vec<&'static dyn AnyMagicStatic>> = std::sync::Mutex::new(Vec::new());
+
+/// Every initialized magic static, in the order they were initialized.
+#[cfg(not(feature = "std"))]
+static INITIALIZED: critical_section::Mutex<core::cell::RefCell<Vec<&'static dyn AnyMagicStatic>>> = critical_section::Mutex::new(core::cell::RefCell::new(Vec::new()));
+
+#[cfg(feature = "drop-at-exit")]
+static REGISTER_ATEXIT: std::sync::Once = std::sync::Once::new();
+
+#[cfg(feature = "drop-at-exit")]
+extern "C" {
+	fn atexit(callback: extern "C" fn()) -> core::ffi::c_int;
+}
+
+fn with_initialized<R>(f: impl FnOnce(&mut Vec<&'static dyn AnyMagicStatic>) -> R) -> R {
+	#[cfg(feature = "std")]
+	return f(&mut INITIALIZED.lock().unwrap_or_else(|poisoned| poisoned.into_inner()));
+
+	#[cfg(not(feature = "std"))]
+	critical_section::with(|cs| f(&mut INITIALIZED.borrow_ref_mut(cs)))
+}
+
+pub(crate) fn register(magic_static: &'static dyn AnyMagicStatic) {
+	#[cfg(feature = "drop-at-exit")]
+	REGISTER_ATEXIT.call_once(|| unsafe {
+		atexit(drop_at_exit);
+	});
+
+	with_initialized(|initialized| initialized.push(magic_static));
+}
+
+/// Forgets a magic static which has been deinitialized, so that it is placed according to when it is initialized again.
+pub(crate) fn unregister(magic_static: &'static dyn AnyMagicStatic) {
+	with_initialized(|initialized| {
+		if let Some(i) = initialized.iter().rposition(|registered| core::ptr::addr_eq(*registered, magic_static)) {
+			initialized.remove(i);
+		}
+	});
+}
+
+/// # Safety
+///
+/// See [`MagicStatic::reset`](crate::MagicStatic::reset).
+pub(crate) unsafe fn deinit_all() {
+	// The lock isn't held while dropping a value, so that its destructor can access other magic statics
+	while let Some(magic_static) = with_initialized(Vec::pop) {
+		magic_static.__deinit_any();
+	}
+}
+
+#[cfg(feature = "drop-at-exit")]
+extern "C" fn drop_at_exit() {
+	unsafe { deinit_all() }
+}
\ No newline at end of file
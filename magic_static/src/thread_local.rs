@@ -0,0 +1,101 @@
+use crate::MagicStatic;
+use std::thread::LocalKey;
+
+/// A thread-local magic static, declared with the `thread_magic_statics!` macro.
+///
+/// Every thread has its own value, which must be initialized by that thread before it is accessed, e.g. with `magic_static::init!` at the start
+/// of the thread (there is no way to run code whenever a thread is spawned.) Like a [`MagicStatic`], accessing the value doesn't check whether it
+/// has been initialized in release builds.
+///
+/// Requires the `std` feature flag.
+pub struct ThreadMagicStatic<T: 'static> {
+	local: &'static LocalKey<MagicStatic<T>>,
+}
+
+impl<T: 'static> ThreadMagicStatic<T> {
+	#[doc(hidden)]
+	#[inline]
+	pub const fn __new(local: &'static LocalKey<MagicStatic<T>>) -> Self {
+		Self { local }
+	}
+
+	/// Returns whether this thread magic static has been initialized on the current thread.
+	#[inline]
+	pub fn is_initialized(&'static self) -> bool {
+		self.local.with(MagicStatic::is_initialized)
+	}
+
+	/// Calls `f` with a reference to the current thread's value of this thread magic static, returning its result.
+	///
+	/// # Panics
+	///
+	/// Panics if the thread's values are being destroyed (e.g. if called from the destructor of another thread local.)
+	#[cfg_attr(debug_assertions, inline)]
+	#[cfg_attr(not(debug_assertions), inline(always))]
+	pub fn with<R>(&'static self, f: impl FnOnce(&T) -> R) -> R {
+		self.local.with(|magic_static| f(magic_static))
+	}
+
+	#[doc(hidden)]
+	#[inline]
+	/// Returns whether the magic static was initialized on the current thread by this call.
+	pub fn __init(&'static self) -> bool {
+		// SAFETY: The reference doesn't outlive this call, which can't outlive the current thread
+		self.local.with(|magic_static| unsafe { &*(magic_static as *const MagicStatic<T>) }.initialize_unregistered(true).unwrap_or_else(|never| match never {}) == Some(true))
+	}
+}
+
+#[macro_export]
+/// Declares thread-local magic statics, which are [`ThreadMagicStatic`]s.
+///
+/// Every thread has its own value of each thread magic static, which must be initialized by that thread before it is accessed (e.g. with
+/// `magic_static::init!` at the start of the thread.) Their values are accessed with [`ThreadMagicStatic::with`].
+///
+/// Requires the `std` feature flag.
+///
+/// # Limitations
+///
+/// Thread magic statics aren't a drop-in per-thread [`MagicStatic`]:
+///
+/// * **They aren't initialized when a thread is spawned.** Rust has no hook which runs code on every new thread (nor does this crate wrap
+///   `std::thread::spawn`), so every thread must initialize the thread magic statics it uses itself, before using them. `#[magic_static::main]`
+///   only initializes them on the main thread.
+/// * **They don't implement `Deref`.** A thread local can only be borrowed for the duration of a closure, so the value is only accessible through
+///   [`ThreadMagicStatic::with`]. Like dereferencing a [`MagicStatic`], `with` doesn't check whether the value has been initialized in release builds.
+///
+/// # Example
+///
+/// ```rust
+/// # #[macro_use] extern crate magic_static;
+/// use std::cell::RefCell;
+///
+/// thread_magic_statics! {
+///     static ref SCRATCH: RefCell<Vec<u8>> = RefCell::new(Vec::with_capacity(4096));
+/// }
+///
+/// #[magic_static::main(SCRATCH)]
+/// fn main() {
+///     SCRATCH.with(|scratch| scratch.borrow_mut().push(1));
+///
+///     std::thread::spawn(|| {
+///         magic_static::init! { SCRATCH };
+///         assert!(SCRATCH.with(|scratch| scratch.borrow().is_empty()));
+///     }).join().unwrap();
+///
+///     assert_eq!(SCRATCH.with(|scratch| scratch.borrow().len()), 1);
+/// }
+/// ```
+macro_rules! thread_magic_statics {
+	{ $($(#[$attr:meta])* $vis:vis static ref $ident:ident: $ty:ty = $expr:expr;)* } => {
+		$(
+			$(#[$attr])*
+			$vis static $ident: $crate::ThreadMagicStatic<$ty> = {
+				$crate::private::thread_local! {
+					static LOCAL: $crate::MagicStatic<$ty> = const { $crate::MagicStatic::<$ty>::named(stringify!($ident), || $expr) };
+				}
+
+				$crate::ThreadMagicStatic::__new(&LOCAL)
+			};
+		)*
+	};
+}
\ No newline at end of file
@@ -0,0 +1,76 @@
+use core::sync::atomic::{AtomicU8, AtomicUsize, Ordering};
+use std::sync::{Condvar, Mutex, PoisonError};
+
+// Shared by every magic static, as waiting for another thread to initialize a magic static is rare
+static LOCK: Mutex<()> = Mutex::new(());
+static CONDVAR: Condvar = Condvar::new();
+
+/// The number of threads waiting on `CONDVAR`, so that finishing an initialization nobody is waiting for doesn't take the lock.
+static WAITERS: AtomicUsize = AtomicUsize::new(0);
+
+/// Counts the current thread as a waiter until it is dropped.
+///
+/// Must be created while holding the lock, and before checking the state of the magic static (see `notify`.)
+struct Waiting;
+impl Waiting {
+	fn start() -> Self {
+		WAITERS.fetch_add(1, Ordering::SeqCst);
+		Self
+	}
+}
+impl Drop for Waiting {
+	fn drop(&mut self) {
+		WAITERS.fetch_sub(1, Ordering::SeqCst);
+	}
+}
+
+/// Blocks the current thread while another thread is initializing the magic static.
+#[cfg_attr(not(feature = "spin-timeout"), allow(unused_variables))]
+pub(crate) fn wait_while_initializing(initialized: &AtomicU8, name: &'static str) {
+	#[cfg(feature = "spin-timeout")]
+	let deadline = crate::spin_timeout::SpinDeadline::start();
+
+	let mut guard = LOCK.lock().unwrap_or_else(PoisonError::into_inner);
+	let _waiting = Waiting::start();
+	while initialized.load(Ordering::SeqCst) == 1 {
+		#[cfg(feature = "spin-timeout")]
+		{
+			guard = CONDVAR.wait_timeout(guard, deadline.remaining(name)).unwrap_or_else(PoisonError::into_inner).0;
+		}
+
+		#[cfg(not(feature = "spin-timeout"))]
+		{
+			guard = CONDVAR.wait(guard).unwrap_or_else(PoisonError::into_inner);
+		}
+	}
+}
+
+/// Blocks the current thread until the magic static has been initialized by another thread, returning its final state (`2`, or `3` if it was poisoned.)
+///
+/// Unlike [`wait_while_initializing`], this also waits for the initialization to start, so `spin-timeout` doesn't apply.
+pub(crate) fn wait_until_initialized(initialized: &AtomicU8) -> u8 {
+	let mut guard = LOCK.lock().unwrap_or_else(PoisonError::into_inner);
+	let _waiting = Waiting::start();
+	loop {
+		match initialized.load(Ordering::SeqCst) {
+			state @ (2 | 3) => return state,
+			_ => guard = CONDVAR.wait(guard).unwrap_or_else(PoisonError::into_inner),
+		}
+	}
+}
+
+/// Wakes up every thread waiting for a magic static to be initialized.
+///
+/// Must be called after the magic static has left the initializing state.
+pub(crate) fn notify() {
+	// A waiter counts itself before checking the state, and the state has been changed before this checks the count, so either the waiter sees the new
+	// state or this sees the waiter. The fence keeps the two in that order even when the state was stored with `Release` (see `relaxed-init`)
+	core::sync::atomic::fence(Ordering::SeqCst);
+	if WAITERS.load(Ordering::SeqCst) == 0 {
+		return;
+	}
+
+	// Taking the lock ensures that a waiting thread is either about to see the new state, or is already waiting
+	drop(LOCK.lock().unwrap_or_else(PoisonError::into_inner));
+	CONDVAR.notify_all();
+}
\ No newline at end of file
@@ -3,19 +3,279 @@
 use proc_macro::TokenStream;
 use quote::ToTokens;
 
+enum MagicStatic {
+	/// `recursive` is set for `mod path::*`, which also initializes the module's children
+	Module { path: syn::Path, recursive: bool },
+	/// `fallible` is set for `path?`, which propagates the error of the magic static's initializer with `?`
+	///
+	/// `lenient` is set for `?path`, which is initialized as usual in strict mode
+	///
+	/// This is an expression path so that associated constants can be listed with `<Type as Trait>::CONST`
+	Item { lenient: Option<syn::Token![?]>, path: syn::ExprPath, fallible: Option<syn::Token![?]> },
+	/// `init = path` calls a function which initializes magic statics itself
+	Function(syn::Path),
+}
+impl syn::parse::Parse for MagicStatic {
+	fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+		if input.peek(syn::Ident) && input.peek2(syn::Token![=]) {
+			let ident = input.parse::<syn::Ident>()?;
+			if ident != "init" {
+				return Err(syn::Error::new(ident.span(), "Unknown option, expected `init = path::to::function`"));
+			}
+			input.parse::<syn::Token![=]>()?;
+			Ok(MagicStatic::Function(input.parse()?))
+		} else if input.peek(syn::Token![mod]) {
+			input.parse::<syn::Token![mod]>()?;
+
+			// `syn::Path` would try to parse the `*` of `mod path::*` as a path segment
+			let mut path = syn::Path {
+				leading_colon: input.parse()?,
+				segments: syn::punctuated::Punctuated::new(),
+			};
+			loop {
+				path.segments.push_value(syn::PathSegment::from(<syn::Ident as syn::ext::IdentExt>::parse_any(input)?));
+				let fork = input.fork();
+				if fork.parse::<syn::Token![::]>().is_ok() && !fork.peek(syn::Token![*]) {
+					path.segments.push_punct(input.parse()?);
+				} else {
+					break;
+				}
+			}
+
+			let recursive = input.peek(syn::Token![::]);
+			if recursive {
+				input.parse::<syn::Token![::]>()?;
+				input.parse::<syn::Token![*]>()?;
+			}
+
+			if input.peek(syn::Token![?]) {
+				return Err(input.error("Modules can't be fallible, list their fallible magic statics with `?` instead"));
+			}
+
+			Ok(MagicStatic::Module { path, recursive })
+		} else {
+			let lenient = input.parse::<Option<syn::Token![?]>>()?;
+			if lenient.is_some() && input.peek(syn::Token![mod]) {
+				return Err(input.error("Modules are never initialized strictly, so they can't be made lenient with `?`"));
+			}
+
+			Ok(MagicStatic::Item {
+				lenient,
+				path: input.parse()?,
+				fallible: input.parse()?,
+			})
+		}
+	}
+}
+
+/// The arguments of `#[magic_static::main]`: an optional leading `strict;` flag, followed by the magic statics to initialize.
+struct MainArgs {
+	strict: bool,
+	magic_statics: syn::punctuated::Punctuated<MagicStatic, syn::Token![,]>,
+}
+impl syn::parse::Parse for MainArgs {
+	fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+		// The flag is followed by a `;` rather than a `,`, so that it can't be mistaken for a magic static called `strict`
+		let fork = input.fork();
+		let strict = matches!(fork.parse::<syn::Ident>(), Ok(ident) if ident == "strict") && fork.peek(syn::Token![;]);
+		if strict {
+			input.parse::<syn::Ident>()?;
+			input.parse::<syn::Token![;]>()?;
+		}
+
+		Ok(MainArgs {
+			strict,
+			magic_statics: syn::punctuated::Punctuated::parse_terminated(input)?,
+		})
+	}
+}
+
+/// Initializes a magic static, awaiting its initializer if it is async when `awaited` is set.
+struct InitMagicStatic<'a> {
+	magic_static: &'a MagicStatic,
+	strict: bool,
+	awaited: bool,
+}
+impl quote::ToTokens for InitMagicStatic<'_> {
+	fn to_tokens(&self, tokens: &mut quote::__private::TokenStream) {
+		let strict = self.strict && !matches!(self.magic_static, MagicStatic::Item { lenient: Some(_), .. });
+		tokens.extend(match (self.magic_static, strict, self.awaited) {
+			(MagicStatic::Module { path, recursive: false }, _, false) => module_path(path, "magic_static"),
+			(MagicStatic::Module { path, recursive: false }, _, true) => { let path = module_path(path, "magic_static_async"); quote::quote! { #path.await } },
+			(MagicStatic::Module { path, recursive: true }, _, false) => module_path(path, "magic_static_recursive"),
+			(MagicStatic::Module { path, recursive: true }, _, true) => { let path = module_path(path, "magic_static_recursive_async"); quote::quote! { #path.await } },
+			(MagicStatic::Item { path, fallible: Some(_), .. }, false, _) => quote::quote! { #path.try_init_checked()? },
+			(MagicStatic::Item { path, fallible: Some(_), .. }, true, _) => quote::quote! { #path.__try_init_strict()? },
+			(MagicStatic::Item { path, fallible: None, .. }, false, false) => quote::quote! { #path.__init() },
+			(MagicStatic::Item { path, fallible: None, .. }, false, true) => quote::quote! { #path.__init_async().await },
+			(MagicStatic::Item { path, fallible: None, .. }, true, false) => quote::quote! { #path.__init_strict() },
+			(MagicStatic::Item { path, fallible: None, .. }, true, true) => quote::quote! { #path.__init_async_strict().await },
+			(MagicStatic::Function(path), ..) => quote::quote! { #path() },
+		});
+	}
+}
+
+/// Calls the function generated by `magic_statics_mod!` called `function` in the module at `path`.
+///
+/// A single identifier (other than `self`, `super` and `crate`) is given a `self::` prefix, so that it can't be mistaken for a crate of the same name.
+fn module_path(path: &syn::Path, function: &str) -> quote::__private::TokenStream {
+	let function = quote::format_ident!("{}", function);
+	match path.segments.first() {
+		Some(first) if path.leading_colon.is_none() && path.segments.len() == 1 && !["self", "super", "crate"].iter().any(|keyword| first.ident == keyword) => {
+			quote::quote! { self::#path::#function() }
+		},
+		_ => quote::quote! { #path::#function() },
+	}
+}
+
 #[proc_macro_attribute]
 /// An attribute that can be attached to your main function which initializes magic statics **in the specified order**.
 ///
-/// Does nothing to a magic static if it has already been initialized.
+/// Does nothing to a magic static if it has already been initialized, unless strict mode is enabled (see below.)
 ///
 /// # Safety
 ///
 /// The following behaviour is considered undefined:
 ///
-/// * Initializing magic statics from multiple threads concurrently.
+/// * Initializing magic statics from multiple threads concurrently, except with `magic_static::init_parallel!` or by accessing lazy magic statics.
 /// * Spawning new threads and accessing magic statics during initialization from them.
 /// * Interior mutability of magic statics where the mutability is not synchronized across multiple threads (e.g. with a Mutex or RwLock.) This is not a problem for single-threaded applications.
 ///
+/// # Strict mode
+///
+/// With `#[magic_static::main(strict; ...)]`, initializing a listed magic static that has already been initialized panics (naming the magic static) instead of doing nothing.
+/// Modules are still initialized as usual.
+///
+/// A magic static listed with a leading `?` (e.g. `#[magic_static::main(strict; ?FOO, BAR)]`) is initialized as usual too, for magic statics which may
+/// legitimately have been initialized already (e.g. by a test harness.)
+///
+/// The flag is separated from the magic statics by a `;`, so `#[magic_static::main(strict)]` initializes a magic static called `strict` as usual, and
+/// `#[magic_static::main(strict; strict)]` initializes it strictly.
+///
+/// # Modules
+///
+/// `mod path` initializes the magic statics of a module, and `mod path::*` its children too (see `magic_statics_mod!`.) The path can start with `self`,
+/// `super` or `crate`, like `mod super::config` or just `mod self`, and is resolved the same way as by `magic_static::init!`.
+///
+/// # Placement
+///
+/// The magic statics are initialized at the start of the function, unless its body contains a `magic_static::init_here!();` statement, which is replaced with their initialization.
+/// This allows setup code (e.g. installing a logger or panic hook) to run first.
+///
+/// # Methods
+///
+/// The attribute can also be attached to methods (e.g. `fn run(&self)` in an `impl` block), whose receiver is left untouched.
+///
+/// # Init order
+///
+/// A constant listing what the function initializes, in order, is generated next to it for diagnostics and tests (e.g. to assert that the startup order
+/// matches expectations.) It is named after the function: `MAIN_INIT_ORDER` for `main`, `MAGIC_STATIC_INIT_ORDER` for a module's `magic_static` function
+/// and `INIT_SERVER_INIT_ORDER` for `pub fn init_server`. Magic statics are listed by their path as written, modules as `mod path` and functions as
+/// `init = path`.
+///
+/// Only `main`, `magic_static` and functions with a visibility (even `pub(self)`) get one. Others may be associated functions of a trait `impl`, which
+/// can't have items the trait doesn't declare, and methods can't have sibling items at all.
+///
+/// ```rust
+/// magic_static::magic_statics! {
+///     static ref CONFIG: &'static str = "config.toml";
+/// }
+///
+/// mod net {
+///     magic_static::magic_statics_mod! {
+///         pub static ref PORT: u16 = 8080;
+///     }
+/// }
+///
+/// #[magic_static::main(CONFIG, mod net)]
+/// fn main() {
+///     assert_eq!(MAIN_INIT_ORDER, ["CONFIG", "mod net"]);
+/// }
+/// ```
+///
+/// # `verify-init`
+///
+/// With the `verify-init` feature flag of `magic_static`, a `main` function annotated with this attribute panics in debug builds if any magic static
+/// (except lazy magic statics) hasn't been initialized once it has initialized the listed magic statics, naming the magic statics which weren't.
+///
+/// # `init = path`
+///
+/// `init = path::to::function` calls a function which initializes magic statics itself (e.g. with `magic_static::init!`), so that a long list of magic
+/// statics can live in a dedicated module instead. It is called in the same order as the other magic statics listed.
+///
+/// ```rust
+/// mod startup {
+///     magic_static::magic_statics! {
+///         pub static ref CONFIG: &'static str = "config.toml";
+///         pub static ref LOGGER: String = format!("logging for {}", *CONFIG);
+///     }
+///
+///     pub fn init() {
+///         magic_static::init! { CONFIG, LOGGER };
+///     }
+/// }
+///
+/// #[magic_static::main(init = startup::init)]
+/// fn main() {
+///     assert_eq!(*startup::LOGGER, "logging for config.toml");
+/// }
+/// ```
+///
+/// # Associated magic statics
+///
+/// Rust doesn't allow `static` items in `impl` blocks, but an associated constant can refer to a magic static, and can then be listed by its path
+/// (`Type::CONST`, or `<Type as Trait>::CONST` for constants of traits.)
+///
+/// ```rust
+/// # use magic_static::MagicStatic;
+/// struct Cache;
+///
+/// trait Store {
+///     const ENTRIES: &'static MagicStatic<Vec<&'static str>>;
+/// }
+///
+/// impl Cache {
+///     const CAPACITY: &'static MagicStatic<usize> = &CACHE_CAPACITY;
+/// }
+///
+/// impl Store for Cache {
+///     const ENTRIES: &'static MagicStatic<Vec<&'static str>> = &CACHE_ENTRIES;
+/// }
+///
+/// #[magic_static::magic_static]
+/// static CACHE_CAPACITY: usize = 16;
+///
+/// #[magic_static::magic_static]
+/// static CACHE_ENTRIES: Vec<&'static str> = Vec::with_capacity(**Cache::CAPACITY);
+///
+/// #[magic_static::main(Cache::CAPACITY, <Cache as Store>::ENTRIES)]
+/// fn main() {
+///     assert_eq!(Cache::ENTRIES.capacity(), 16);
+/// }
+/// ```
+///
+/// # Fallible magic statics
+///
+/// A magic static listed with a trailing `?` (e.g. `#[magic_static::main(PORT?)]`) is initialized with [`MagicStatic::try_init_checked`](../magic_static/struct.MagicStatic.html#method.try_init_checked)
+/// and its error is propagated with the `?` operator, so the function must return a `Result` whose error type can be converted from the error of its initializer.
+/// Fallible magic statics listed without `?` panic if their initializer fails.
+///
+/// ```rust
+/// magic_static::magic_statics! {
+///     static try ref PORT: Result<u16, std::num::ParseIntError> = std::env::var("PORT").as_deref().unwrap_or("8080").parse()?;
+/// }
+///
+/// #[magic_static::main(PORT?)]
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     println!("Listening on port {}", *PORT);
+///     Ok(())
+/// }
+/// ```
+///
+/// # `async`
+///
+/// When attached to an `async` function, magic statics with an async initializer (e.g. `static async ref`) are initialized by awaiting them.
+///
 /// # Example
 ///
 /// ```rust
@@ -53,6 +313,7 @@ use quote::ToTokens;
 ///     foo::MAGIC,
 ///     foo::BAR,
 ///     mod baz // This will initialize all magic statics in `baz`
+///             // (`mod baz::*` would also initialize the children of `baz`, see `magic_statics_mod!`)
 /// )]
 /// fn main() {
 ///     println!("Hello, world!");
@@ -60,61 +321,472 @@ use quote::ToTokens;
 /// ```
 pub fn main(attr: TokenStream, item: TokenStream) -> TokenStream {
 	let mut func = syn::parse_macro_input!(item as syn::ItemFn);
-	let attr = attr.to_string();
+	let MainArgs { strict, magic_statics } = syn::parse_macro_input!(attr as MainArgs);
 
-	enum MagicStatic {
-		Module(syn::Path),
-		Item(syn::Path),
+	let mut modules = std::collections::HashSet::new();
+	let mut items = std::collections::HashSet::new();
+	let mut duplicates: Option<syn::Error> = None;
+	for magic_static in magic_statics.iter() {
+		let (path, seen, what) = match magic_static {
+			MagicStatic::Module { path, .. } => (path.to_token_stream(), &mut modules, "module"),
+			MagicStatic::Item { path, .. } => (path.to_token_stream(), &mut items, "magic static"),
+			MagicStatic::Function(..) => continue,
+		};
+		if !seen.insert(path.to_string()) {
+			let error = syn::Error::new_spanned(path, format!("This {} is already listed", what));
+			match &mut duplicates {
+				Some(duplicates) => duplicates.combine(error),
+				None => duplicates = Some(error),
+			}
+		}
 	}
-	impl quote::ToTokens for MagicStatic {
-		fn to_tokens(&self, tokens: &mut quote::__private::TokenStream) {
-			match self {
-				MagicStatic::Module(path) => tokens.extend(quote::quote! { #path::magic_static() }),
-				MagicStatic::Item(path) => tokens.extend(quote::quote! { #path.__init() }),
+	if let syn::ReturnType::Default = func.sig.output {
+		for magic_static in magic_statics.iter() {
+			if let MagicStatic::Item { fallible: Some(question), .. } = magic_static {
+				let error = syn::Error::new_spanned(question, "Fallible magic statics can only be listed if the function returns a `Result`");
+				match &mut duplicates {
+					Some(duplicates) => duplicates.combine(error),
+					None => duplicates = Some(error),
+				}
 			}
 		}
 	}
+	if let Some(duplicates) = duplicates {
+		let mut tokens = duplicates.to_compile_error();
+		func.to_tokens(&mut tokens);
+		return tokens.into();
+	}
 
-	let mut magic_statics = vec![];
-	for item in attr.split(',').map(|path| path.trim()) {
-		if let Some(item) = item.strip_prefix("mod ").map(str::trim) {
-			if item.contains("::") {
-				magic_statics.push(MagicStatic::Module(syn::parse_str(item).expect("Expected path to a module containing an accessible `magic_static` function")));
-			} else {
-				magic_statics.push(MagicStatic::Module(syn::parse_str(&format!("self::{}", item)).expect("Expected path to a module containing an accessible `magic_static` function")));
+	let init = |awaited| magic_statics.iter().map(move |magic_static| InitMagicStatic { magic_static, strict, awaited });
+	let awaited = init(true).collect::<Vec<_>>();
+	let initialized = magic_statics.iter().map(|magic_static| match magic_static {
+		MagicStatic::Module { path, .. } => module_path(path, "magic_static_initialized"),
+		MagicStatic::Item { path, .. } => quote::quote! { #path.is_initialized() },
+		MagicStatic::Function(..) => quote::quote! { true },
+	});
+
+	// Methods are left alone apart from initializing the magic statics, as they can't be a module's `magic_static` function or the program's `main`
+	let method = func.sig.receiver().is_some();
+
+	// The items generated next to the function must be compiled out along with it, e.g. if `#[cfg]` follows this attribute
+	let cfgs = func.attrs.iter().filter(|attr| attr.path.is_ident("cfg")).cloned().collect::<Vec<_>>();
+
+	// A hand-written `magic_static` function for a module also gets the other functions generated by `magic_statics_mod!`
+	let companion = if func.sig.ident == "magic_static" && func.sig.asyncness.is_none() && !method {
+		let vis = &func.vis;
+		let output = &func.sig.output;
+		Some(quote::quote! {
+			#(#cfgs)*
+			#[doc(hidden)]
+			#vis async fn magic_static_async() #output {
+				#(#awaited;)*
+				magic_static()
 			}
-		} else {
-			magic_statics.push(MagicStatic::Item(syn::parse_str(item).expect("Expected path to magic static")));
+
+			#(#cfgs)*
+			#[doc(hidden)]
+			#vis fn magic_static_initialized() -> bool {
+				true #(&& #initialized)*
+			}
+
+			#(#cfgs)*
+			#[doc(hidden)]
+			#vis fn magic_static_recursive() #output {
+				magic_static()
+			}
+
+			#(#cfgs)*
+			#[doc(hidden)]
+			#vis async fn magic_static_recursive_async() #output {
+				magic_static_async().await
+			}
+		})
+	} else {
+		None
+	};
+
+	let init = init(func.sig.asyncness.is_some());
+	let init = quote::quote! { #(#init;)* };
+
+	let mut markers = func.block.stmts.iter().enumerate().filter(|(_, stmt)| is_init_here(stmt)).map(|(i, _)| i);
+	let position = markers.next();
+	if let Some(duplicate) = markers.next() {
+		let mut tokens = syn::Error::new_spanned(&func.block.stmts[duplicate], "`init_here!()` can only be used once").to_compile_error();
+		func.to_tokens(&mut tokens);
+		return tokens.into();
+	}
+
+	// With the `verify-init` feature, `main` checks that every magic static has been initialized
+	let verify = if func.sig.ident == "main" && !method {
+		Some(quote::quote! { ::magic_static::__magic_static_verify_init!(); })
+	} else {
+		None
+	};
+
+	let init = syn::parse(quote::quote! {
+		{
+			#init
+			#verify
 		}
+	}.into()).expect("Internal error");
+
+	match position {
+		Some(position) => func.block.stmts[position] = init,
+		None => func.block.stmts.insert(0, init),
 	}
 
-	func.block.stmts.insert(
-		0,
-		syn::parse(quote::quote! {
-			{
-				#(#magic_statics;)*
+	// The init order is named after the function, so that several annotated functions can live in one module. Methods can't have sibling items, and
+	// functions without a visibility may be in a trait `impl`, where only the trait's items can be
+	let fn_ident = syn::ext::IdentExt::unraw(&func.sig.ident);
+	let order = if method || (matches!(func.vis, syn::Visibility::Inherited) && fn_ident != "main" && fn_ident != "magic_static") {
+		None
+	} else {
+		let ident = quote::format_ident!("{}_INIT_ORDER", fn_ident.to_string().to_uppercase());
+		let vis = &func.vis;
+		let doc = format!("The magic statics initialized by `{}`, in order.", fn_ident);
+		let entries = magic_statics.iter().map(init_order_entry);
+		Some(quote::quote! {
+			#(#cfgs)*
+			#[doc = #doc]
+			#[allow(dead_code)]
+			#vis const #ident: &[&str] = &[#(#entries),*];
+		})
+	};
+
+	let mut tokens = func.into_token_stream();
+	tokens.extend(companion);
+	tokens.extend(order);
+	tokens.into()
+}
+
+/// How a listed magic static appears in the init order constant generated by `#[magic_static::main]`: its path as written, `mod path` (or `mod path::*`)
+/// for modules, and `init = path` for functions.
+fn init_order_entry(magic_static: &MagicStatic) -> String {
+	fn path_to_string(path: &impl ToTokens) -> String {
+		let path = path.to_token_stream().to_string().replace(" :: ", "::").replace("< ", "<").replace(" >", ">");
+		match path.strip_prefix(":: ") {
+			Some(path) => format!("::{}", path),
+			None => path,
+		}
+	}
+
+	match magic_static {
+		MagicStatic::Module { path, recursive: false } => format!("mod {}", path_to_string(path)),
+		MagicStatic::Module { path, recursive: true } => format!("mod {}::*", path_to_string(path)),
+		MagicStatic::Item { path, .. } => path_to_string(path),
+		MagicStatic::Function(path) => format!("init = {}", path_to_string(path)),
+	}
+}
+
+/// Whether a statement is a `magic_static::init_here!()` marker.
+fn is_init_here(stmt: &syn::Stmt) -> bool {
+	let mac = match stmt {
+		syn::Stmt::Item(syn::Item::Macro(syn::ItemMacro { mac, .. })) => mac,
+		syn::Stmt::Semi(syn::Expr::Macro(syn::ExprMacro { mac, .. }), _) | syn::Stmt::Expr(syn::Expr::Macro(syn::ExprMacro { mac, .. })) => mac,
+		_ => return false,
+	};
+	mac.path.segments.last().map(|segment| segment.ident == "init_here").unwrap_or(false)
+}
+
+enum MagicStaticOption {
+	DependsOn(syn::punctuated::Punctuated<syn::Path, syn::Token![,]>),
+	/// `before(...)` if set, `after(...)` otherwise
+	Order(bool, syn::punctuated::Punctuated<syn::Path, syn::Token![,]>),
+	Name(syn::LitStr),
+	Group(syn::LitStr),
+	Lazy,
+	Check(syn::Ident, bool),
+	ArrayFromFn(syn::Ident),
+	/// `unsafe(in_place)`, as the initializer must fully initialize the value
+	InPlace(syn::Token![unsafe]),
+	/// `align = N`, which stores the value in an `Aligned` wrapper
+	Align(syn::LitInt),
+}
+impl syn::parse::Parse for MagicStaticOption {
+	fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+		if input.peek(syn::Token![unsafe]) {
+			let unsafety = input.parse::<syn::Token![unsafe]>()?;
+			let content;
+			syn::parenthesized!(content in input);
+			let option = content.parse::<syn::Ident>()?;
+			if option != "in_place" {
+				return Err(syn::Error::new(option.span(), "Unknown unsafe option, expected `unsafe(in_place)`"));
 			}
-		}.into()).expect("Internal error"),
-	);
+			return Ok(MagicStaticOption::InPlace(unsafety));
+		}
+
+		let option = input.parse::<syn::Ident>()?;
+		match option.to_string().as_str() {
+			"depends_on" => {
+				let content;
+				syn::parenthesized!(content in input);
+				Ok(MagicStaticOption::DependsOn(content.parse_terminated(syn::Path::parse)?))
+			},
+
+			"before" | "after" => {
+				let content;
+				syn::parenthesized!(content in input);
+				Ok(MagicStaticOption::Order(option == "before", content.parse_terminated(syn::Path::parse)?))
+			},
+
+			"name" => {
+				input.parse::<syn::Token![=]>()?;
+				Ok(MagicStaticOption::Name(input.parse()?))
+			},
+
+			"lazy" => Ok(MagicStaticOption::Lazy),
+
+			"array_from_fn" => Ok(MagicStaticOption::ArrayFromFn(option)),
 
-	func.into_token_stream().into()
+			"check" => {
+				input.parse::<syn::Token![=]>()?;
+				let check = input.parse::<syn::Ident>()?;
+				match check.to_string().as_str() {
+					"always" => Ok(MagicStaticOption::Check(option, true)),
+					"never" => Ok(MagicStaticOption::Check(option, false)),
+					_ => Err(syn::Error::new(check.span(), "Expected `always` or `never`")),
+				}
+			},
+
+			"group" => {
+				input.parse::<syn::Token![=]>()?;
+				Ok(MagicStaticOption::Group(input.parse()?))
+			},
+
+			"align" => {
+				input.parse::<syn::Token![=]>()?;
+				let align = input.parse::<syn::LitInt>()?;
+				match align.base10_parse::<u32>() {
+					Ok(bytes) if bytes.is_power_of_two() && bytes <= 65536 => Ok(MagicStaticOption::Align(align)),
+					_ => Err(syn::Error::new(align.span(), "Expected a power of two between 1 and 65536")),
+				}
+			},
+
+			_ => Err(syn::Error::new(option.span(), "Unknown option, expected `depends_on(...)`, `before(...)`, `after(...)`, `name = \"...\"`, `group = \"...\"`, `lazy`, `check = ...`, `array_from_fn`, `unsafe(in_place)` or `align = ...`")),
+		}
+	}
+}
+
+/// Parses a `static` without a type (e.g. `static X = compute();`), which `syn::ItemStatic` rejects with an unhelpful "expected `:`"
+fn parse_untyped_static(input: syn::parse::ParseStream) -> syn::Result<syn::Ident> {
+	input.call(syn::Attribute::parse_outer)?;
+	input.parse::<syn::Visibility>()?;
+	input.parse::<syn::Token![static]>()?;
+	input.parse::<Option<syn::Token![mut]>>()?;
+	let ident = input.parse::<syn::Ident>()?;
+	input.parse::<syn::Token![=]>()?;
+	input.parse::<syn::Expr>()?;
+	input.parse::<syn::Token![;]>()?;
+	Ok(ident)
 }
 
 #[proc_macro_attribute]
-pub fn magic_static(_attr: TokenStream, item: TokenStream) -> TokenStream {
-	let mut func = syn::parse_macro_input!(item as syn::ItemStatic);
-
-	let ty = func.ty;
-	let expr = func.expr;
-
-	func.ty = Box::new(syn::parse_quote! { ::magic_static::MagicStatic<#ty> });
-	func.expr = Box::new(syn::parse_quote! {
-		::magic_static::MagicStatic {
-			initialized: ::magic_static::__magic_static_initialized!(),
-			value: ::core::cell::UnsafeCell::new(::core::mem::MaybeUninit::uninit()),
-			init: || #expr
+/// Turns a `static` into a magic static.
+///
+/// # Options
+///
+/// * `depends_on(path::to::MAGIC, ...)` - The magic statics this magic static accesses during its initialization. These will always be initialized before it, and a dependency cycle will panic when the magic static is initialized.
+/// * `before(path::to::MAGIC, ...)` / `after(path::to::MAGIC, ...)` - Ordering constraints honoured by `magic_static::init_all!()`, which then initializes
+///   this magic static before (or after) the given ones, for declaring the order locally instead of in one global list. Unlike `depends_on`, they don't
+///   make initializing one magic static initialize the other. A cycle is a compile error when it can be detected from this attribute alone, and panics
+///   (naming the cycle) in `init_all!()` otherwise. Requires the `auto-register` feature flag, and isn't honoured by the `startup` feature flag.
+/// * `name = "foo::MAGIC"` - The name of this magic static in panic messages and diagnostics (e.g. `magic_static::init_timings()`.) Defaults to its identifier.
+/// * `lazy` - Makes this magic static a `LazyMagicStatic`, which is initialized when its value is first accessed if it hasn't been initialized yet. Every access then checks whether it has been initialized.
+/// * `group = "net"` - The group this magic static belongs to, which is initialized with `magic_static::init_group!("net")`. Requires the `auto-register` feature flag.
+/// * `array_from_fn` - Initializes an array element by element with the initializer, a closure taking the index of each element (see `magic_static::array_from_fn!`),
+///   so that a large lookup table is written directly into the magic static instead of passing through the stack.
+/// * `unsafe(in_place)` - Initializes the value in place with the initializer, a closure taking a `&mut MaybeUninit<T>` (see `MagicStatic::new_in_place`),
+///   for large values other than arrays. **The closure must fully initialize the value**, hence `unsafe`.
+/// * `align = 64` - Stores the value at (at least) the given alignment, a power of two up to 65536, e.g. to keep a lookup table accessed in hot loops on
+///   its own cache lines. The magic static then holds a `magic_static::Aligned<T, _>`, which dereferences to the value. Can be combined with the other options.
+/// * `check = always` / `check = never` - Makes this magic static a `CheckedMagicStatic`, whose accesses always or never check whether it has been initialized, whatever the build profile. Can't be combined with `lazy` or `static mut`.
+///
+/// # Captures
+///
+/// The initializer is called when the magic static is initialized rather than where it is declared, so it can't refer to local variables. rustc reports this
+/// as "attempt to use a non-constant value in a constant" (E0435); use a `const` or another magic static for the value instead. The attribute can't report
+/// this more clearly itself, as macros can't tell local variables from other names and rustc rejects the capture before checking anything the attribute
+/// generates.
+///
+/// # Associated statics
+///
+/// Rust rejects `static` items in `impl` blocks before this attribute sees them. Declare the magic static next to the type instead, and refer to it
+/// from an associated constant (see `#[magic_static::main]`.)
+///
+/// # `static mut`
+///
+/// A `static mut` becomes an immutable `static` whose value can be mutated with the unsafe `MagicStatic::get_mut_unchecked`. This requires the `unsafe-mut` feature and **is only sound in single-threaded applications.**
+///
+/// # Example
+///
+/// ```rust
+/// #[magic_static]
+/// static MAGIC: usize = 42;
+///
+/// #[magic_static(depends_on(MAGIC))]
+/// static DOUBLE_MAGIC: usize = *MAGIC * 2;
+///
+/// #[magic_static::main(DOUBLE_MAGIC)] // This will initialize `MAGIC` first
+/// fn main() {
+///     assert_eq!(*DOUBLE_MAGIC, 84);
+/// }
+/// ```
+pub fn magic_static(attr: TokenStream, item: TokenStream) -> TokenStream {
+	let mut func = match syn::parse::<syn::ItemStatic>(item.clone()) {
+		Ok(func) => func,
+		Err(err) => {
+			let err = match syn::parse::Parser::parse(parse_untyped_static, item) {
+				Ok(ident) => syn::Error::new(ident.span(), format!("Magic statics need an explicit type annotation, like any `static`: `static {}: Type = ...;`", ident)),
+				Err(_) => err,
+			};
+			return err.to_compile_error().into();
+		},
+	};
+	let options = syn::parse_macro_input!(attr with syn::punctuated::Punctuated::<MagicStaticOption, syn::Token![,]>::parse_terminated);
+
+	let mut depends_on = Vec::new();
+	let mut before = Vec::new();
+	let mut after = Vec::new();
+	let mut name = None;
+	let mut group = None;
+	let mut lazy = false;
+	let mut check = None;
+	let mut array_from_fn = None;
+	let mut in_place = None;
+	let mut align = None;
+	for option in options {
+		match option {
+			MagicStaticOption::DependsOn(paths) => depends_on.extend(paths),
+			MagicStaticOption::Order(true, paths) => before.extend(paths),
+			MagicStaticOption::Order(false, paths) => after.extend(paths),
+			MagicStaticOption::Name(lit) => name = Some(lit.value()),
+			MagicStaticOption::Group(lit) => group = Some(lit),
+			MagicStaticOption::Lazy => lazy = true,
+			MagicStaticOption::Check(option, value) => check = Some((option, value)),
+			MagicStaticOption::ArrayFromFn(option) => array_from_fn = Some(option),
+			MagicStaticOption::InPlace(unsafety) => in_place = Some(unsafety),
+			MagicStaticOption::Align(lit) => align = Some(lit),
 		}
-	});
+	}
+
+	if let (Some(option), Some(_)) = (&array_from_fn, &in_place) {
+		return syn::Error::new(option.span(), "`array_from_fn` can't be combined with `unsafe(in_place)`").to_compile_error().into();
+	}
+
+	// Mutable access goes through `MagicStatic::get_mut_unchecked` instead
+	let mutable = func.mutability.take().is_some();
 
-	func.into_token_stream().into()
+	// Cycles which don't involve other magic statics' constraints can be caught here, the rest are caught by `init_all!`
+	let is_self = |path: &syn::Path| path.leading_colon.is_none() && match path.segments.len() {
+		1 => path.segments[0].ident == func.ident,
+		2 => path.segments[0].ident == "self" && path.segments[1].ident == func.ident,
+		_ => false,
+	};
+	if let Some(path) = before.iter().chain(after.iter()).find(|path| is_self(path)) {
+		return syn::Error::new_spanned(path, "A magic static can't be initialized before or after itself").to_compile_error().into();
+	}
+	if let Some(path) = before.iter().find(|path| after.iter().any(|other| other.to_token_stream().to_string() == path.to_token_stream().to_string())) {
+		return syn::Error::new_spanned(path, "A magic static can't be initialized both before and after the same magic static").to_compile_error().into();
+	}
+
+	if let Some((option, _)) = &check {
+		if lazy {
+			return syn::Error::new(option.span(), "`check` can't be combined with `lazy`, which always checks").to_compile_error().into();
+		}
+		if mutable {
+			return syn::Error::new(option.span(), "`check` can't be used on a `static mut`").to_compile_error().into();
+		}
+	}
+
+	let mut ty = func.ty;
+	let mut expr = func.expr;
+	let ident = func.ident.clone();
+	let name = name.unwrap_or_else(|| ident.to_string());
+	let cfgs = func.attrs.iter().filter(|attr| attr.path.is_ident("cfg")).cloned().collect::<Vec<_>>();
+
+	// The value is wrapped to be over-aligned, and in-place initializers are given the wrapped value to write
+	if let Some(align) = &align {
+		let marker = quote::format_ident!("Align{}", align.base10_digits());
+		let inner = ty;
+		ty = Box::new(syn::parse_quote! { ::magic_static::Aligned<#inner, ::magic_static::private::#marker> });
+		if in_place.is_some() {
+			*expr = syn::parse_quote! {
+				|aligned| {
+					const INIT: fn(&mut ::core::mem::MaybeUninit<#inner>) = #expr;
+					INIT(::magic_static::Aligned::__uninit_inner(aligned))
+				}
+			};
+		} else if array_from_fn.is_none() {
+			*expr = syn::parse_quote! { ::magic_static::Aligned::new(#expr) };
+		}
+	}
+
+	// In-place initializers are bound to a constant first so that they aren't in the `unsafe` block
+	let init: syn::Expr = if array_from_fn.is_some() && align.is_some() {
+		syn::parse_quote! {
+			{
+				const INIT: fn(&mut ::core::mem::MaybeUninit<#ty>) = |aligned| ::magic_static::private::__fill_array(::magic_static::Aligned::__uninit_inner(aligned), #expr);
+				unsafe { ::magic_static::MagicStatic::<#ty>::named_in_place(#name, INIT) }
+			}
+		}
+	} else if array_from_fn.is_some() {
+		syn::parse_quote! {
+			{
+				const INIT: fn(&mut ::core::mem::MaybeUninit<#ty>) = |array| ::magic_static::private::__fill_array(array, #expr);
+				unsafe { ::magic_static::MagicStatic::<#ty>::named_in_place(#name, INIT) }
+			}
+		}
+	} else if in_place.is_some() {
+		syn::parse_quote! {
+			{
+				const INIT: fn(&mut ::core::mem::MaybeUninit<#ty>) = #expr;
+				unsafe { ::magic_static::MagicStatic::<#ty>::named_in_place(#name, INIT) }
+			}
+		}
+	} else {
+		syn::parse_quote! { ::magic_static::MagicStatic::<#ty>::named(#name, || #expr) }
+	};
+	let magic_static: syn::Expr = syn::parse_quote! {
+		#init.__depends_on(&[#(&#depends_on),*]).__require_sync()
+	};
+	if lazy {
+		func.ty = Box::new(syn::parse_quote! { ::magic_static::LazyMagicStatic<#ty> });
+		// Bound to a constant first so that the initializer isn't in the `unsafe` block
+		func.expr = Box::new(syn::parse_quote! {
+			{
+				#[allow(clippy::declare_interior_mutable_const)]
+				const __MAGIC_STATIC: ::magic_static::MagicStatic<#ty> = #magic_static;
+				unsafe { ::magic_static::LazyMagicStatic::__new(__MAGIC_STATIC) }
+			}
+		});
+	} else if let Some((_, check)) = check {
+		func.ty = Box::new(syn::parse_quote! { ::magic_static::CheckedMagicStatic<#ty, #check> });
+		func.expr = Box::new(syn::parse_quote! { ::magic_static::CheckedMagicStatic::__new(#magic_static) });
+	} else {
+		func.ty = Box::new(syn::parse_quote! { ::magic_static::MagicStatic<#ty> });
+		func.expr = Box::new(magic_static);
+	}
+
+	let group = group.map(|group| quote::quote! { group = #group, });
+
+	let mut tokens = func.to_token_stream();
+	if mutable {
+		tokens.extend(quote::quote! { ::magic_static::__magic_static_mut!(); });
+	}
+	let cfgs = quote::quote! { #(#cfgs)* };
+	tokens.extend(quote::quote! {
+		#cfgs
+		::magic_static::__magic_static_register!(#group #ident);
+
+		#(
+			#cfgs
+			::magic_static::__magic_static_init_before!(#ident, #before);
+		)*
+
+		#(
+			#cfgs
+			::magic_static::__magic_static_init_before!(#after, #ident);
+		)*
+	});
+	tokens.into()
 }
@@ -7,6 +7,40 @@ static NAKED_FOO: u32 = { println!("Hello world from naked static!"); 11 };
 #[magic_static]
 static NAKED_FOO_2: u32 = { println!("Hello world from naked static 2!"); 12 };
 
+#[magic_static(depends_on(NAKED_FOO))]
+static NAKED_DEPENDENT: u32 = *NAKED_FOO + 1;
+
+static MANUAL: magic_static::MagicStatic<u32> = magic_static::MagicStatic::new(|| 13);
+static LAZY: magic_static::MagicStatic<u32> = magic_static::MagicStatic::new(|| 14);
+static TABLE: magic_static::MagicStatic<[u32; 1 << 16]> = magic_static::array_from_fn!(|i| i as u32 * *LAZY);
+static POLL_GATE: std::sync::Barrier = std::sync::Barrier::new(2);
+static POLLED: magic_static::MagicStatic<u32> = magic_static::MagicStatic::new(|| {
+	POLL_GATE.wait();
+	POLL_GATE.wait();
+	17
+});
+static WAITED: magic_static::MagicStatic<u32> = magic_static::MagicStatic::new(|| 18);
+static CONTEXT_WORKERS: magic_static::MagicStatic<usize> = magic_static::MagicStatic::named_with_context("CONTEXT_WORKERS", |args: &Vec<&str>| args.len());
+static CONTEXT_FIRST: magic_static::MagicStatic<String> = magic_static::MagicStatic::with_context(|args: &Vec<&str>| args[0].to_uppercase());
+
+magic_statics! {
+	pub lazy static ref ON_DEMAND: Vec<u32> = vec![15];
+}
+
+#[magic_static(lazy)]
+static NAKED_ON_DEMAND: u32 = ON_DEMAND[0] + 1;
+
+static LAZY_INITS: std::sync::atomic::AtomicUsize = std::sync::atomic::AtomicUsize::new(0);
+static LAZY_GATE: std::sync::Barrier = std::sync::Barrier::new(32);
+
+magic_statics! {
+	pub lazy static ref CONTENDED_ON_DEMAND: Vec<usize> = {
+		LAZY_INITS.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+		std::thread::sleep(std::time::Duration::from_millis(50));
+		(0..1024).collect()
+	};
+}
+
 mod foo {
 	magic_statics! {
 		pub static ref BAR: usize = {
@@ -42,6 +76,7 @@ mod other_module {
 
 		pub static ref OOH: usize = 0;
 		pub static ref OK: usize = 1;
+		pub static ref STRICT: usize = 2;
 	}
 
 	#[magic_static::main(WOW)]
@@ -58,6 +93,571 @@ mod auto_module {
 		pub static ref OOH: usize = 0;
 		pub static ref OK: usize = 1;
 	}
+
+	magic_statics_mod!(init_group = first_group {
+		pub static ref FIRST: usize = 1;
+	});
+
+	magic_statics_mod!(init_group = cfg_group {
+		/// Documented!
+		#[cfg(all())]
+		pub static ref ENABLED: usize = 1;
+
+		#[cfg(any())]
+		pub static ref DISABLED: usize = 0;
+	});
+
+	magic_statics_mod!(init_group = second_group {
+		pub static ref SECOND: usize = 2;
+		pub static ref THIRD: usize = 3;
+	});
+}
+
+magic_statics! {
+	pub static ref CONTENDED: Vec<usize> = (0..4096).collect();
+}
+
+static DROPPED: std::sync::atomic::AtomicUsize = std::sync::atomic::AtomicUsize::new(0);
+
+pub struct DropCounter;
+impl Drop for DropCounter {
+	fn drop(&mut self) {
+		DROPPED.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+	}
+}
+
+magic_statics! {
+	pub static ref DROPPY: DropCounter = DropCounter;
+}
+
+magic_statics! {
+	pub static ref POISONED: usize = panic!("Poisoned!");
+	pub static ref OBSERVED: magic_static::InitState = OBSERVED.state();
+	pub static ref NEVER: usize = 0;
+}
+
+magic_statics! {
+	#[check = always]
+	pub static ref ALWAYS_CHECKED: usize = 18;
+
+	#[check = never]
+	#[depends_on(ALWAYS_CHECKED)]
+	pub static try ref NEVER_CHECKED: Result<usize, std::num::ParseIntError> = "19".parse::<usize>()? + *ALWAYS_CHECKED;
+}
+
+#[magic_static(check = always)]
+static NAKED_ALWAYS_CHECKED: usize = 20;
+
+#[magic_static(array_from_fn, depends_on(LAZY))]
+static NAKED_TABLE: [u32; 1 << 20] = |i| (i as u32) ^ *LAZY;
+
+#[magic_static(unsafe(in_place), lazy)]
+static NAKED_BUFFER: [u8; 1 << 20] = |buffer| unsafe { buffer.as_mut_ptr().write_bytes(7, 1) };
+
+#[magic_static(align = 64)]
+static ALIGNED_BYTE: u8 = 1;
+
+#[magic_static(align = 4096, array_from_fn)]
+static ALIGNED_TABLE: [u16; 3] = |i| i as u16 * 2;
+
+#[magic_static(align = 128, unsafe(in_place), check = always)]
+static ALIGNED_IN_PLACE: [u8; 5] = |buffer| { buffer.write([3; 5]); };
+
+magic_statics! {
+	#[depends_on(CYCLE_B)]
+	pub static ref CYCLE_A: usize = 0;
+
+	#[depends_on(self::CYCLE_A)]
+	pub static ref CYCLE_B: usize = 0;
+}
+
+mod constants {
+	magic_statics_mod! {
+		pub const ref ANSWER: usize = 42;
+		pub static ref DOUBLED: usize = *ANSWER * 2;
+	}
+}
+
+mod alternatives {
+	magic_statics_mod! {
+		/// Chosen by `cfg`
+		pub static ref BUILD: &'static str = #[cfg(debug_assertions)] { "debug" } else { "release" };
+		pub lazy static ref LAZY_BUILD: &'static str = #[cfg(not(debug_assertions))] { "release" } else { "debug" };
+		pub const ref CONST_DISABLED: usize = #[cfg(any())] { 0 } else { 1 };
+		pub static try ref TRY_ENABLED: Result<u8, std::num::ParseIntError> = #[cfg(all())] { "2".parse()? } else { "fallback".parse()? };
+	}
+}
+
+magic_statics! {
+	pub static env ref MAGIC_STATIC_TEST_PORT: u16 = 8080;
+	pub static env ref MAGIC_STATIC_TEST_UNSET: u8 = 1;
+	pub static env("MAGIC_STATIC_TEST_NAME") ref ENV_NAME: String = "default".to_string();
+}
+
+static ENV_WARNINGS: std::sync::Mutex<Vec<String>> = std::sync::Mutex::new(Vec::new());
+
+magic_statics! {
+	pub static ref RAW_TIMEOUT: &'static str = "30";
+	pub lazy static ref RAW_RETRIES: String = "3".to_string();
+	pub static ref MAYBE_PROXY: Option<String> = None;
+	pub lazy static ref MAYBE_MIRROR: Option<&'static str> = Some("mirror");
+}
+
+mod lazy_group {
+	pub static INITIALIZED: std::sync::atomic::AtomicUsize = std::sync::atomic::AtomicUsize::new(0);
+
+	magic_statics_mod!(lazy {
+		pub static ref FIRST: usize = INITIALIZED.fetch_add(1, std::sync::atomic::Ordering::SeqCst) + 1;
+		pub static ref SECOND: usize = *FIRST + INITIALIZED.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+		pub static ref REENTRANT: usize = *LAST * 2;
+		pub static ref LAST: usize = 10;
+		pub const ref CONSTANT: usize = 1;
+	});
+}
+
+mod declaration_order {
+	magic_statics_mod! {
+		pub static ref A: usize = 1;
+		pub static ref B: usize = *A + 1;
+		pub static ref C: usize = *A + *B;
+	}
+
+	magic_statics_mod!(init_group = parsed {
+		pub static ref FIRST: usize = 10;
+		pub static try ref SECOND: Result<usize, std::num::ParseIntError> = *FIRST + "1".parse::<usize>()?;
+	});
+}
+
+magic_statics! {
+	pub static ref OUT_OF_ORDER: usize = *NOT_YET;
+	pub static ref NOT_YET: usize = 0;
+}
+
+magic_statics! {
+	pub static try ref FALLIBLE: Result<u16, std::num::ParseIntError> = "1234".parse::<u16>()? + 1;
+	pub static ref INFALLIBLE: u16 = 1;
+	pub static try ref FAILING: Result<u16, std::num::ParseIntError> = "nope".parse()?;
+}
+
+fn init_fallible() -> Result<(), std::num::ParseIntError> {
+	magic_static::init! { INFALLIBLE };
+	magic_static::init_try! { FALLIBLE, }
+	assert_eq!(*FALLIBLE, 1235);
+
+	magic_static::init_try! { FAILING }
+	unreachable!()
+}
+
+magic_statics! {
+	pub static try ref MAIN_FALLIBLE: Result<u16, std::num::ParseIntError> = "80".parse()?;
+	pub static try ref MAIN_FAILING: Result<u16, std::num::ParseIntError> = "eighty".parse()?;
+}
+
+#[magic_static::main(INFALLIBLE, MAIN_FALLIBLE?, MAIN_FAILING?)]
+fn init_main_fallible() -> Result<(), std::num::ParseIntError> {
+	unreachable!()
+}
+
+mod async_module {
+	magic_statics_mod! {
+		pub static async ref ASYNC: usize = {
+			std::future::ready(()).await;
+			7
+		};
+
+		pub static ref SYNC: usize = 8;
+	}
+}
+
+magic_statics! {
+	pub static async ref ASYNC_TOP: usize = *async_module::ASYNC * 6;
+	pub static async ref ASYNC_SYNCLY: usize = 0;
+}
+
+#[magic_static::main(
+	mod async_module,
+	ASYNC_TOP,
+	mod some_module
+)]
+async fn init_async() {
+	assert_eq!(*async_module::SYNC, 8);
+}
+
+#[magic_static::main(ASYNC_SYNCLY)]
+async fn init_async_syncly() {}
+
+mod collections {
+	use std::collections::HashMap;
+
+	magic_statics! {
+		pub static ref CACHE: HashMap<String, Vec<u8>> = HashMap::new();
+		pub static ref LENGTHS: HashMap<&'static str, (usize, Option<Vec<&'static str>>)> = [("magic", (5, None))].into_iter().collect();
+	}
+
+	magic_statics_mod! {
+		pub const ref EMPTY: Option<HashMap<u8, u8>> = None;
+		pub static try ref PARSED: Result<HashMap<u8, u16>, std::num::ParseIntError> = [(1, "1234".parse()?)].into_iter().collect();
+		pub static ref NESTED: std::sync::Mutex<HashMap<u8, HashMap<u8, Vec<u8>>>> = Default::default();
+	}
+}
+
+#[magic_static]
+static NAKED_MAP: std::collections::HashMap<&'static str, std::collections::HashMap<u8, u8>> = std::collections::HashMap::new();
+
+#[magic_static::main(collections::CACHE, collections::LENGTHS, collections::PARSED?, mod collections, NAKED_MAP)]
+fn init_collections() -> Result<(), std::num::ParseIntError> {
+	assert!(collections::CACHE.is_empty());
+	assert_eq!(collections::LENGTHS["magic"], (5, None));
+	assert_eq!(collections::PARSED[&1], 1234);
+	collections::NESTED.lock().unwrap().entry(0).or_default().insert(1, vec![2]);
+	assert!(NAKED_MAP.is_empty());
+	Ok(())
+}
+
+static TEARDOWN: std::sync::Mutex<Vec<&'static str>> = std::sync::Mutex::new(Vec::new());
+
+pub struct TornDown(&'static str);
+
+impl Drop for TornDown {
+	fn drop(&mut self) {
+		TEARDOWN.lock().unwrap().push(self.0);
+	}
+}
+
+magic_statics! {
+	#[depends_on(TEARDOWN_B)]
+	pub static ref TEARDOWN_A: TornDown = {
+		let _ = &*TEARDOWN_B;
+		TornDown("A")
+	};
+	pub static ref TEARDOWN_B: TornDown = TornDown("B");
+}
+
+mod startup {
+	magic_statics! {
+		pub static ref CONFIG: &'static str = "config.toml";
+		pub static ref LOGGER: String = format!("logging for {}", *CONFIG);
+	}
+
+	pub fn init() {
+		magic_static::init! { CONFIG };
+	}
+}
+
+#[magic_static::main(init = startup::init, startup::LOGGER)]
+pub(crate) fn init_with_fn() {}
+
+struct Cache;
+
+trait Store {
+	const ENTRIES: &'static magic_static::MagicStatic<Vec<&'static str>>;
+}
+
+impl Cache {
+	const CAPACITY: &'static magic_static::MagicStatic<usize> = &CACHE_CAPACITY;
+}
+
+impl Store for Cache {
+	const ENTRIES: &'static magic_static::MagicStatic<Vec<&'static str>> = &CACHE_ENTRIES;
+}
+
+#[magic_static]
+static CACHE_CAPACITY: usize = 16;
+
+#[magic_static]
+static CACHE_ENTRIES: Vec<&'static str> = Vec::with_capacity(**Cache::CAPACITY);
+
+#[magic_static::main(Cache::CAPACITY, <Cache as Store>::ENTRIES)]
+pub(crate) fn init_associated() {}
+
+magic_statics! {
+	pub static ref SERVICE_READY: bool = true;
+}
+
+trait Service {
+	fn start();
+}
+
+struct Server;
+
+impl Service for Server {
+	// Trait `impl`s can't have the init order constant
+	#[magic_static::main(SERVICE_READY)]
+	fn start() {}
+}
+
+mod init_orders {
+	magic_static::magic_statics! {
+		pub static ref FIRST: usize = 1;
+		pub static ref SECOND: usize = 2;
+	}
+
+	#[magic_static::main(FIRST)]
+	pub fn magic_static() {}
+
+	#[magic_static::main(mod self, SECOND)]
+	pub fn main() {}
+}
+
+magic_statics! {
+	pub static ref APP_NAME: &'static str = "app";
+	pub static ref APP_RUNS: std::sync::atomic::AtomicUsize = std::sync::atomic::AtomicUsize::new(0);
+}
+
+struct App {
+	runs: usize,
+}
+
+impl App {
+	#[magic_static::main(APP_NAME)]
+	fn name(&self) -> &'static str {
+		*APP_NAME
+	}
+
+	#[magic_static::main(strict; ?APP_RUNS)]
+	fn run(&mut self) {
+		self.runs += 1;
+		magic_static::init_here!();
+		APP_RUNS.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+	}
+}
+
+mod plugins {
+	magic_statics! {
+		pub static ref REGISTERED: usize = 1;
+		pub lazy static ref LAZILY_REGISTERED: usize = *REGISTERED + 1;
+		pub const ref CONSTANT: usize = 3;
+	}
+}
+
+mod aliased {
+	magic_statics_mod! {
+		pub static ref ORIGINAL: usize = 7;
+		pub static ref OTHER: usize = 8;
+		pub static ref THIRD: usize = 9;
+	}
+}
+
+mod prelude {
+	pub use crate::aliased::ORIGINAL as RENAMED;
+	pub use crate::aliased::*;
+	pub(crate) use crate::aliased as module;
+}
+
+#[magic_static::main(prelude::OTHER)]
+fn init_reexported() {}
+
+static SETUP_DONE: std::sync::atomic::AtomicBool = std::sync::atomic::AtomicBool::new(false);
+
+magic_statics! {
+	pub static ref AFTER_SETUP: bool = SETUP_DONE.load(std::sync::atomic::Ordering::SeqCst);
+}
+
+#[magic_static::main(AFTER_SETUP)]
+fn init_after_setup() {
+	assert!(!AFTER_SETUP.is_initialized());
+	SETUP_DONE.store(true, std::sync::atomic::Ordering::SeqCst);
+	magic_static::init_here!();
+	assert!(*AFTER_SETUP);
+}
+
+magic_statics! {
+	pub static ref ATTRIBUTED: u32 = 94;
+}
+
+/// Other attributes must survive the init block being inserted, and a `#[cfg]` must also apply to the init order
+#[magic_static::main(ATTRIBUTED)]
+#[inline]
+#[cfg_attr(all(), deny(unused_variables))]
+#[cfg(not(any()))]
+#[must_use]
+pub(crate) fn init_attributed() -> u32 {
+	*ATTRIBUTED
+}
+
+#[magic_static::main(ATTRIBUTED)]
+#[cfg(any())]
+fn init_attributed() -> u32 {
+	compile_error!("compiled out")
+}
+
+mod cfg_module {
+	magic_static::magic_statics! {
+		pub static ref CFG_ENABLED: bool = true;
+	}
+
+	#[magic_static::main(CFG_ENABLED)]
+	#[cfg(not(any()))]
+	pub fn magic_static() {}
+
+	#[magic_static::main(CFG_ENABLED)]
+	#[cfg(any())]
+	pub fn magic_static() {}
+}
+
+magic_statics! {
+	pub static ref STRICTLY: usize = 5;
+}
+
+#[magic_static::main(strict; STRICTLY, mod some_module)]
+fn init_strictly() {}
+
+magic_statics! {
+	pub static ref PREINITIALIZED: usize = 6;
+	pub static ref STRICT_AFTER_LENIENT: usize = *PREINITIALIZED + 1;
+}
+
+#[magic_static::main(strict; ?PREINITIALIZED, STRICT_AFTER_LENIENT)]
+pub(crate) fn init_leniently() {}
+
+#[magic_static(name = "crate::NAMED")]
+static NAMED: usize = 6;
+
+#[magic_static::main(strict; NAMED)]
+fn init_named_strictly() {}
+
+mod named_strict {
+	magic_statics! {
+		#[allow(non_upper_case_globals)]
+		pub static ref strict: usize = 8;
+	}
+
+	#[magic_static::main(strict)]
+	pub fn init() {}
+
+	#[magic_static::main(strict; strict)]
+	pub fn init_strictly() {}
+}
+
+mod tree {
+	magic_statics_mod! {
+		children = [branch, leaf_group];
+		pub static ref ROOT: usize = 1;
+	}
+
+	magic_statics_mod!(init_group = leaf_group {
+		pub static ref LEAF: usize = 2;
+	});
+
+	pub mod branch {
+		magic_statics_mod! {
+			children = [twig,];
+			pub static ref BRANCH: usize = 3;
+		}
+
+		pub mod twig {
+			magic_statics_mod! {
+				pub static ref TWIG: usize = 4;
+			}
+		}
+	}
+}
+
+#[magic_static::main(mod tree::*)]
+pub(crate) fn init_tree() {}
+
+// Module paths must work the same way in `#[magic_static::main]` and `magic_static::init!`
+mod relative_paths {
+	pub mod by_attribute {
+		magic_statics_mod! {
+			children = [inner];
+			pub static ref FROM_SUPER: usize = 1;
+		}
+
+		pub mod inner {
+			magic_statics_mod! {
+				pub static ref FROM_SELF: usize = 2;
+			}
+
+			pub mod child {
+				magic_statics_mod! {
+					pub static ref FROM_CHILD: usize = 3;
+				}
+			}
+
+			pub mod grandchild {
+				magic_statics_mod! {
+					pub static ref FROM_CHILD: usize = 4;
+				}
+			}
+
+			#[magic_static::main(mod self, mod self::child, mod grandchild, mod super::*, mod crate::relative_paths::by_attribute::deep::deeper)]
+			pub fn init() {}
+		}
+
+		pub mod deep {
+			pub mod deeper {
+				magic_statics_mod! {
+					pub static ref FROM_CRATE: usize = 5;
+				}
+			}
+		}
+	}
+
+	pub mod by_macro {
+		magic_statics_mod! {
+			children = [inner];
+			pub static ref FROM_SUPER: usize = 1;
+		}
+
+		pub mod inner {
+			magic_statics_mod! {
+				pub static ref FROM_SELF: usize = 2;
+			}
+
+			pub mod child {
+				magic_statics_mod! {
+					pub static ref FROM_CHILD: usize = 3;
+				}
+			}
+
+			pub mod grandchild {
+				magic_statics_mod! {
+					pub static ref FROM_CHILD: usize = 4;
+				}
+			}
+
+			pub fn init() -> usize {
+				magic_static::init! { mod self, mod self::child, mod grandchild, mod super::*, mod crate::relative_paths::by_macro::deep::deeper }
+			}
+		}
+
+		pub mod deep {
+			pub mod deeper {
+				magic_statics_mod! {
+					pub static ref FROM_CRATE: usize = 5;
+				}
+			}
+		}
+	}
+}
+
+mod restricted {
+	magic_statics_mod!(pub(crate) {
+		pub(super) static ref HIDDEN: usize = 11;
+	});
+
+	magic_statics_mod!(pub(super) init_group = inner {
+		pub(super) static ref INNER: usize = 12;
+	});
+}
+
+thread_magic_statics! {
+	pub static ref THREAD_ID: std::thread::ThreadId = std::thread::current().id();
+}
+
+fn block_on(future: impl std::future::Future<Output = ()>) {
+	let mut future = std::pin::pin!(future);
+	while future.as_mut().poll(&mut std::task::Context::from_waker(std::task::Waker::noop())).is_pending() {}
+}
+
+mod dependencies {
+	magic_statics_mod! {
+		#[depends_on(BASE)]
+		pub static ref DEPENDENT: usize = *BASE + *super::TOP_LEVEL;
+
+		pub static ref BASE: usize = 1;
+	}
 }
 
 magic_statics! {
@@ -71,13 +671,51 @@ magic_statics! {
 	NAKED_FOO_2,
 	TOP_LEVEL,
 	foo::BAR,
-	mod some_module
+	mod some_module,
 )]
 fn main() {
 	assert_eq!(*NAKED_FOO_2, 12);
 	assert_eq!(*foo::BAR, 42);
+	assert_eq!(foo::BAR.clone_inner(), 42);
 	assert!(std::panic::catch_unwind(|| magic_static::init! { foo::BAR }).is_ok());
 
+	assert!(!NAKED_FOO.is_initialized());
+
+	assert_eq!(other_module::STRICT.try_init(), Ok(()));
+	assert_eq!(other_module::STRICT.try_init(), Err(magic_static::AlreadyInitialized));
+	assert_eq!(NAKED_FOO.get(), None);
+	assert!(!LAZY.is_initialized());
+	assert_eq!(LAZY.state(), magic_static::InitState::Uninitialized);
+	assert_eq!(*LAZY.get_or_init(), 14);
+	assert_eq!(*LAZY.get_or_init(), 14);
+	assert!(LAZY.is_initialized());
+	assert_eq!(LAZY.state(), magic_static::InitState::Initialized);
+	let lazy: &'static u32 = LAZY.get_static();
+	assert!(std::ptr::eq(lazy, &*LAZY));
+	magic_static::init! { TABLE };
+	assert_eq!(TABLE[3], 42);
+	unsafe { TABLE.reinit() };
+	assert_eq!(TABLE.iter().map(|&x| x as u64).sum::<u64>(), 14 * ((1 << 16) - 1) * (1 << 15));
+
+	assert!(!ON_DEMAND.is_initialized());
+	assert_eq!(*NAKED_ON_DEMAND, 16);
+	assert!(ON_DEMAND.is_initialized());
+	assert_eq!(ON_DEMAND.len(), 1);
+	let on_demand: &'static [u32] = ON_DEMAND.project(|on_demand| on_demand.as_slice());
+	assert_eq!(on_demand, [15]);
+	assert_eq!(ON_DEMAND.clone_inner(), [15]);
+	assert_eq!(format!("{:?}", ON_DEMAND), "[15]");
+	assert_eq!(ON_DEMAND.cmp(&ON_DEMAND), std::cmp::Ordering::Equal);
+	assert!(std::collections::HashSet::from([&ON_DEMAND]).contains(&&ON_DEMAND));
+	assert_eq!(magic_static::init! { ON_DEMAND, NAKED_ON_DEMAND }, 0);
+
+	let sums = (0..32).map(|_| std::thread::spawn(|| {
+		LAZY_GATE.wait();
+		CONTENDED_ON_DEMAND.iter().sum::<usize>()
+	})).collect::<Vec<_>>();
+	assert!(sums.into_iter().all(|sum| sum.join().unwrap() == 1023 * 1024 / 2));
+	assert_eq!(LAZY_INITS.load(std::sync::atomic::Ordering::SeqCst), 1);
+
 	magic_static::init! {
 		NAKED_FOO,
 
@@ -85,11 +723,54 @@ fn main() {
 		crate::other_module::OOH,
 		self::other_module::OK,
 
-		mod auto_module
-	}
+		mod auto_module,
+		mod auto_module::first_group
+	};
+
+	assert!(constants::ANSWER.is_initialized());
+	assert_eq!(*constants::ANSWER, 42);
+	assert_eq!(format!("{:?} {:#x}", constants::ANSWER, constants::ANSWER), "42 0x2a");
+	assert!(constants::ANSWER == magic_static::ConstMagicStatic::new(42));
+	assert!(std::collections::HashSet::from([&constants::ANSWER]).contains(&&constants::ANSWER));
+	assert_eq!(magic_static::init! { mod constants }, 1);
+	assert_eq!(*constants::DOUBLED, 84);
+	assert!(constants::magic_static_initialized());
 
+	magic_static::init! { mod auto_module::cfg_group };
+	assert!(auto_module::ENABLED.is_initialized());
+
+	assert!(auto_module::FIRST.is_initialized());
+	assert!(!auto_module::SECOND.is_initialized());
+	assert!(auto_module::first_group::magic_static_initialized());
+	assert!(!auto_module::second_group::magic_static_initialized());
+	magic_static::init! { mod auto_module::second_group };
+	assert!(auto_module::second_group::magic_static_initialized());
+	assert!(auto_module::cfg_group::magic_static_initialized());
+	assert!(some_module::magic_static_initialized());
+	assert_eq!(*auto_module::SECOND + *auto_module::THIRD, 5);
+	assert_eq!(auto_module::FIRST, auto_module::ENABLED);
+	assert_ne!(auto_module::SECOND, auto_module::THIRD);
+	assert!(auto_module::SECOND < auto_module::THIRD);
+	assert_eq!(AsRef::<usize>::as_ref(&auto_module::SECOND), &2);
+	assert_eq!(std::borrow::Borrow::<usize>::borrow(&auto_module::THIRD), &3);
+	assert_eq!(std::collections::BTreeSet::from([&auto_module::THIRD, &auto_module::SECOND]).into_iter().next(), Some(&auto_module::SECOND));
+	assert!(std::collections::HashSet::from([&auto_module::FIRST, &auto_module::ENABLED]).contains(&&auto_module::ENABLED));
+
+	assert!(NAKED_FOO.is_initialized());
+	assert_eq!(NAKED_FOO.get(), Some(&11));
+	assert_eq!(NAKED_FOO.try_init(), Err(magic_static::AlreadyInitialized));
 	assert_eq!(*NAKED_FOO, 11);
 
+	magic_static::init! { MANUAL };
+	assert_eq!(*MANUAL, 13);
+	assert_eq!(MANUAL.as_ptr(), &*MANUAL as *const u32);
+	assert_eq!(unsafe { *MANUAL.as_ptr() }, 13);
+	unsafe { MANUAL.reset() };
+	assert!(!MANUAL.is_initialized());
+	unsafe { MANUAL.reset() };
+	assert_eq!(magic_static::init! { MANUAL }, 1);
+	assert_eq!(*MANUAL, 13);
+
 	{
 		let barrier = std::sync::Arc::new(std::sync::Barrier::new(3));
 		let barrier_a = barrier.clone();
@@ -111,8 +792,297 @@ fn main() {
 		assert_eq!(n, 3);
 	}
 
+	{
+		const THREADS: usize = 32;
+		let barrier = std::sync::Arc::new(std::sync::Barrier::new(THREADS));
+		let threads = (0..THREADS).map(|_| {
+			let barrier = barrier.clone();
+			std::thread::spawn(move || {
+				barrier.wait();
+				magic_static::init! { CONTENDED };
+				CONTENDED.iter().sum::<usize>()
+			})
+		}).collect::<Vec<_>>();
+		for thread in threads {
+			assert_eq!(thread.join().unwrap(), 4096 * 4095 / 2);
+		}
+	}
+	{
+		let initializer = std::thread::spawn(|| POLLED.poll_init());
+		POLL_GATE.wait();
+		assert!(!POLLED.poll_init());
+		assert_eq!(POLLED.state(), magic_static::InitState::Initializing);
+		POLL_GATE.wait();
+		assert!(initializer.join().unwrap());
+		assert!(POLLED.poll_init());
+		assert_eq!(*POLLED, 17);
+	}
+	{
+		let waiters = (0..4).map(|_| std::thread::spawn(|| {
+			WAITED.wait_initialized();
+			*WAITED
+		})).collect::<Vec<_>>();
+		std::thread::sleep(std::time::Duration::from_millis(20));
+		assert!(!WAITED.is_initialized());
+		magic_static::init! { WAITED };
+		assert!(waiters.into_iter().all(|waiter| waiter.join().unwrap() == 18));
+		WAITED.wait_initialized();
+	}
+	{
+		assert!(std::panic::catch_unwind(|| magic_static::init! { CONTEXT_WORKERS }).is_err());
+		assert!(std::panic::catch_unwind(|| CONTEXT_WORKERS.init_with(&1u8)).is_err());
+		assert_eq!(CONTEXT_WORKERS.state(), magic_static::InitState::Uninitialized);
+		assert_eq!(CONTEXT_WORKERS.raw_state(), 0);
+
+		let args = vec!["build", "--release"];
+		assert_eq!(magic_static::init_with! { &args => CONTEXT_WORKERS, CONTEXT_FIRST, MANUAL }, 2);
+		assert_eq!(*CONTEXT_WORKERS, 2);
+		assert_eq!(CONTEXT_WORKERS.raw_state(), 2);
+		assert_eq!(*CONTEXT_FIRST, "BUILD");
+		assert_eq!(magic_static::init_with! { &args => CONTEXT_WORKERS }, 0);
+	}
+	let mut sum = 0;
+	for i in &CONTENDED {
+		sum += i;
+	}
+	assert_eq!(sum, 4096 * 4095 / 2);
+
+	assert!(std::panic::catch_unwind(|| magic_static::init! { POISONED }).is_err());
+	let poisoned = std::panic::catch_unwind(|| magic_static::init! { POISONED }).unwrap_err();
+	assert_eq!(poisoned.downcast_ref::<String>().unwrap(), "The magic static `POISONED` is poisoned: its initializer panicked");
+	assert!(!POISONED.is_initialized());
+	assert_eq!(POISONED.state(), magic_static::InitState::Poisoned);
+	assert_eq!(POISONED.try_get(), Err(magic_static::Poisoned));
+	assert_eq!(POISONED.raw_state(), 3);
+	magic_static::init! { OBSERVED };
+	assert_eq!(*OBSERVED, magic_static::InitState::Initializing);
+	assert_eq!(unsafe { POISONED.replace(1) }, None);
+	assert_eq!(unsafe { POISONED.replace(2) }, Some(1));
+	assert_eq!(*POISONED, 2);
+	assert_eq!(POISONED.try_get(), Ok(Some(&2)));
+
+	magic_static::init! { DROPPY };
+	unsafe {
+		magic_static::deinit! { DROPPY, mod auto_module, mod auto_module::cfg_group, }
+		magic_static::deinit! { DROPPY }
+	}
+	assert_eq!(DROPPED.load(std::sync::atomic::Ordering::SeqCst), 1);
+	{
+		let mut local = magic_static::MagicStatic::new(|| DropCounter);
+		assert!(local.init_local());
+		std::mem::forget(local.into_inner());
+		assert_eq!(DROPPED.load(std::sync::atomic::Ordering::SeqCst), 1);
+
+		let mut local = magic_static::MagicStatic::new(|| DropCounter);
+		local.init_local();
+		drop(local);
+		assert_eq!(DROPPED.load(std::sync::atomic::Ordering::SeqCst), 2);
+		DROPPED.store(1, std::sync::atomic::Ordering::SeqCst);
+	}
+	assert!(!DROPPY.is_initialized());
+	assert!(!auto_module::WOW.is_initialized());
+	assert!(!auto_module::ENABLED.is_initialized());
+	assert_eq!(magic_static::init! { DROPPY, DROPPY, mod auto_module, mod auto_module::cfg_group, mod some_module }, 5);
+	assert!(DROPPY.is_initialized());
+	assert_eq!(magic_static::init! { DROPPY, mod auto_module }, 0);
+	assert_eq!(magic_static::init! { DROPPY, mod auto_module, }, 0);
+	assert_eq!(magic_static::init! { mod auto_module, }, 0);
+	assert_eq!(magic_static::init! { mod some_module::*, }, 0);
+	assert_eq!(magic_static::init! { DROPPY, }, 0);
+	unsafe { DROPPY.reinit() };
+	assert!(DROPPY.is_initialized());
+	assert_eq!(DROPPED.load(std::sync::atomic::Ordering::SeqCst), 2);
+
+	magic_static::init! { NAKED_DEPENDENT, mod dependencies };
+	assert_eq!(*NAKED_DEPENDENT, 12);
+	assert_eq!(*dependencies::DEPENDENT, 1338);
+
+	let cycle = std::panic::catch_unwind(|| magic_static::init! { CYCLE_A }).unwrap_err();
+	assert_eq!(cycle.downcast_ref::<String>().map(String::as_str), Some("Dependency cycle detected between magic statics: CYCLE_A -> CYCLE_B -> CYCLE_A"));
+
+	assert!(init_fallible().is_err());
+	assert!(init_main_fallible().is_err());
+	assert_eq!(init_collections(), Ok(()));
+	assert_eq!(magic_static::init! { crate::prelude::RENAMED }, 1);
+	assert!(aliased::ORIGINAL.is_initialized());
+	init_reexported();
+	assert!(aliased::OTHER.is_initialized());
+	assert_eq!(magic_static::init! { mod prelude::module }, 1);
+	assert_eq!(*aliased::THIRD, 9);
+
+	std::env::set_var("MAGIC_STATIC_TEST_PORT", "not a port");
+	std::env::set_var("MAGIC_STATIC_TEST_NAME", "custom");
+	magic_static::set_env_warning_hook(|variable, value, error| ENV_WARNINGS.lock().unwrap().push(format!("{}={} ({})", variable, value, error)));
+	magic_static::init! { MAGIC_STATIC_TEST_PORT, MAGIC_STATIC_TEST_UNSET, ENV_NAME };
+	assert_eq!((*MAGIC_STATIC_TEST_PORT, *MAGIC_STATIC_TEST_UNSET, ENV_NAME.as_str()), (8080, 1, "custom"));
+	assert_eq!(*ENV_WARNINGS.lock().unwrap(), ["MAGIC_STATIC_TEST_PORT=not a port (invalid digit found in string)"]);
+
+	magic_static::init! { RAW_TIMEOUT };
+	assert_eq!((RAW_TIMEOUT.parse::<u64>(), RAW_RETRIES.parse::<u8>()), (Ok(30), Ok(3)));
+	magic_static::init! { MAYBE_PROXY };
+	assert_eq!((MAYBE_PROXY.as_option(), MAYBE_MIRROR.as_option()), (None, Some(&"mirror")));
+	assert!(ENV_NAME.parse::<u16>().is_err());
+
+	assert_eq!(magic_static::init! { mod alternatives }, 3);
+	assert_eq!(*alternatives::BUILD, if cfg!(debug_assertions) { "debug" } else { "release" });
+	assert_eq!(*alternatives::LAZY_BUILD, *alternatives::BUILD);
+	assert_eq!(*alternatives::CONST_DISABLED + *alternatives::TRY_ENABLED as usize, 3);
+
+	assert_eq!(magic_static::init! { mod declaration_order, mod declaration_order::parsed }, 5);
+	assert_eq!((*declaration_order::B, *declaration_order::C, *declaration_order::SECOND), (2, 3, 11));
+
+	assert!(!lazy_group::FIRST.is_initialized());
+	assert_eq!(*lazy_group::SECOND, 2);
+	assert!(lazy_group::magic_static_initialized());
+	assert_eq!((*lazy_group::FIRST, *lazy_group::REENTRANT, *lazy_group::CONSTANT), (1, 20, 1));
+	assert_eq!(magic_static::init! { mod lazy_group }, 0);
+
+	init_with_fn();
+	assert_eq!(*startup::LOGGER, "logging for config.toml");
+
+	init_associated();
+	assert!(CACHE_CAPACITY.is_initialized());
+	assert_eq!(Cache::ENTRIES.capacity(), 16);
+
+	assert_eq!(INIT_ASSOCIATED_INIT_ORDER, ["Cache::CAPACITY", "<Cache as Store>::ENTRIES"]);
+	assert_eq!(INIT_WITH_FN_INIT_ORDER, ["init = startup::init", "startup::LOGGER"]);
+	assert_eq!(INIT_LENIENTLY_INIT_ORDER, ["PREINITIALIZED", "STRICT_AFTER_LENIENT"]);
+	assert_eq!((INIT_TREE_INIT_ORDER, some_module::MAGIC_STATIC_INIT_ORDER), (&["mod tree::*"][..], &["WOW"][..]));
+	assert_eq!(MAIN_INIT_ORDER, ["NAKED_FOO_2", "TOP_LEVEL", "foo::BAR", "mod some_module"]);
+
+	<Server as Service>::start();
+	assert!(*SERVICE_READY);
+
+	init_orders::main();
+	assert_eq!(*init_orders::FIRST + *init_orders::SECOND, 3);
+	assert_eq!((init_orders::MAGIC_STATIC_INIT_ORDER, init_orders::MAIN_INIT_ORDER), (&["FIRST"][..], &["mod self", "SECOND"][..]));
+
+	let mut app = App { runs: 0 };
+	assert_eq!(app.name(), "app");
+	app.run();
+	app.run();
+	assert_eq!((app.runs, APP_RUNS.load(std::sync::atomic::Ordering::Relaxed)), (2, 2));
+
+	let mut plugins: Vec<&'static dyn magic_static::MagicStaticInit> = vec![&plugins::REGISTERED];
+	plugins.extend([&plugins::LAZILY_REGISTERED as &dyn magic_static::MagicStaticInit, &plugins::CONSTANT]);
+	assert!(!plugins.iter().all(|plugin| plugin.is_initialized()));
+	assert_eq!(magic_static::init_many(&plugins), 2);
+	assert!(plugins.iter().all(|plugin| plugin.is_initialized()));
+	assert_eq!(*plugins::REGISTERED + *plugins::LAZILY_REGISTERED + *plugins::CONSTANT, 6);
+	assert!(collections::magic_static_initialized());
+	assert_eq!(*MAIN_FALLIBLE, 80);
+	assert!(!MAIN_FAILING.is_initialized());
+	assert!(!FAILING.is_initialized());
+	assert!(std::panic::catch_unwind(|| magic_static::init! { FAILING }).is_err());
+
+	init_after_setup();
+	relative_paths::by_attribute::inner::init();
+	assert_eq!(relative_paths::by_macro::inner::init(), 5);
+	assert_eq!(relative_paths::by_attribute::inner::INIT_INIT_ORDER, ["mod self", "mod self::child", "mod grandchild", "mod super::*", "mod crate::relative_paths::by_attribute::deep::deeper"]);
+	for (attribute, declarative) in [
+		(&relative_paths::by_attribute::FROM_SUPER, &relative_paths::by_macro::FROM_SUPER),
+		(&relative_paths::by_attribute::inner::FROM_SELF, &relative_paths::by_macro::inner::FROM_SELF),
+		(&relative_paths::by_attribute::inner::child::FROM_CHILD, &relative_paths::by_macro::inner::child::FROM_CHILD),
+		(&relative_paths::by_attribute::inner::grandchild::FROM_CHILD, &relative_paths::by_macro::inner::grandchild::FROM_CHILD),
+		(&relative_paths::by_attribute::deep::deeper::FROM_CRATE, &relative_paths::by_macro::deep::deeper::FROM_CRATE),
+	] {
+		assert!(attribute.is_initialized() && declarative.is_initialized());
+		assert_eq!(**attribute, **declarative);
+	}
+	assert_eq!(init_attributed(), 94);
+	assert_eq!(INIT_ATTRIBUTED_INIT_ORDER, ["ATTRIBUTED"]);
+	assert!(!cfg_module::magic_static_initialized());
+	magic_static::init! { mod cfg_module };
+	assert!(cfg_module::magic_static_initialized());
+	assert!(*cfg_module::CFG_ENABLED);
+
+	assert!(!THREAD_ID.is_initialized());
+	assert_eq!(magic_static::init! { THREAD_ID }, 1);
+	assert_eq!(magic_static::init! { THREAD_ID }, 0);
+	std::thread::spawn(|| {
+		assert!(!THREAD_ID.is_initialized());
+		magic_static::init! { THREAD_ID };
+		assert_eq!(THREAD_ID.with(|id| *id), std::thread::current().id());
+	}).join().unwrap();
+	assert_eq!(THREAD_ID.with(|id| *id), std::thread::current().id());
+
+	assert_eq!(magic_static::init! { mod tree::branch::* }, 2);
+	assert!(!tree::ROOT.is_initialized());
+	init_tree();
+	assert_eq!(*tree::ROOT + *tree::LEAF + *tree::branch::BRANCH + *tree::branch::twig::TWIG, 10);
+
+	assert_eq!(magic_static::init! { mod restricted, mod restricted::inner }, 2);
+	assert!(restricted::magic_static_initialized() && restricted::inner::magic_static_initialized());
+	assert_eq!(*restricted::HIDDEN + *restricted::INNER, 23);
+
+	init_strictly();
+	assert_eq!(*STRICTLY, 5);
+	let strict = std::panic::catch_unwind(init_strictly).unwrap_err();
+	assert_eq!(strict.downcast_ref::<String>().map(String::as_str), Some("The magic static `STRICTLY` has already been initialized! It is initialized in strict mode by `#[magic_static::main(strict; ...)]`, which expects to be the first to initialize it."));
+
+	init_named_strictly();
+	let strict = std::panic::catch_unwind(init_named_strictly).unwrap_err();
+	assert!(strict.downcast_ref::<String>().unwrap().starts_with("The magic static `crate::NAMED` has already been initialized!"));
+	assert_eq!(NAMED.name(), "crate::NAMED");
+
+	magic_static::init! { PREINITIALIZED };
+	init_leniently();
+	assert_eq!(*STRICT_AFTER_LENIENT, 7);
+	let strict = std::panic::catch_unwind(init_leniently).unwrap_err();
+	assert!(strict.downcast_ref::<String>().unwrap().starts_with("The magic static `STRICT_AFTER_LENIENT` has already been initialized!"));
+	assert_eq!((STRICTLY.name(), ON_DEMAND.name(), MANUAL.name()), ("STRICTLY", "ON_DEMAND", "<unnamed>"));
+
+	named_strict::init();
+	named_strict::init();
+	assert_eq!(*named_strict::strict, 8);
+	let strict = std::panic::catch_unwind(named_strict::init_strictly).unwrap_err();
+	assert!(strict.downcast_ref::<String>().unwrap().starts_with("The magic static `strict` has already been initialized!"));
+
+	block_on(init_async());
+	assert_eq!(*ASYNC_TOP, 42);
+	assert!(std::panic::catch_unwind(|| magic_static::init! { ASYNC_SYNCLY }).is_err());
+	assert!(!ASYNC_SYNCLY.is_initialized());
+	block_on(init_async_syncly());
+	assert_eq!(*ASYNC_SYNCLY, 0);
+
+	let uninitialized = std::panic::catch_unwind(|| *ALWAYS_CHECKED).unwrap_err();
+	assert!(uninitialized.downcast_ref::<String>().unwrap().starts_with("The magic static `ALWAYS_CHECKED` has not been initialized yet!"));
+	assert!(std::panic::catch_unwind(|| NAKED_ALWAYS_CHECKED.get_static()).is_err());
+	assert_eq!(magic_static::init! { NEVER_CHECKED, NAKED_ALWAYS_CHECKED }, 2);
+	let _: &magic_static::CheckedMagicStatic<usize, false, std::num::ParseIntError> = &NEVER_CHECKED;
+	assert_eq!(*NEVER_CHECKED + *NAKED_ALWAYS_CHECKED, 57);
+	assert_eq!(NEVER_CHECKED.clone_inner(), 37);
+	assert_eq!(format!("{} {:x}", NEVER_CHECKED, NAKED_ALWAYS_CHECKED), "37 14");
+	assert!(ALWAYS_CHECKED < NAKED_ALWAYS_CHECKED);
+	assert!(std::collections::HashSet::from([&ALWAYS_CHECKED]).contains(&&ALWAYS_CHECKED));
+
+	magic_static::init! { NAKED_TABLE };
+	assert_eq!((NAKED_TABLE[0], NAKED_TABLE[(1 << 20) - 1]), (14, 0xFFFFF ^ 14));
+	assert!(NAKED_BUFFER.iter().all(|byte| *byte == 7));
+	magic_static::init! { ALIGNED_BYTE, ALIGNED_TABLE, ALIGNED_IN_PLACE };
+	assert_eq!((**ALIGNED_BYTE, ALIGNED_TABLE[2], ALIGNED_IN_PLACE.len()), (1, 4, 5));
+	assert_eq!(&**ALIGNED_BYTE as *const u8 as usize % 64, 0);
+	assert_eq!(ALIGNED_TABLE.as_ptr() as usize % 4096, 0);
+	assert_eq!(ALIGNED_IN_PLACE.as_ptr() as usize % 128, 0);
+	assert_eq!(**ALIGNED_IN_PLACE, [3; 5]);
+
+	if cfg!(debug_assertions) {
+		let uninitialized = std::panic::catch_unwind(|| *NEVER).unwrap_err();
+		assert!(uninitialized.downcast_ref::<String>().unwrap().starts_with("The magic static `NEVER` has not been initialized yet!"));
+		assert!(std::panic::catch_unwind(|| NEVER.get_static()).is_err());
+		assert!(std::panic::catch_unwind(|| NEVER.project(|never| never)).is_err());
+
+		let out_of_order = std::panic::catch_unwind(|| magic_static::init! { OUT_OF_ORDER }).unwrap_err();
+		assert!(out_of_order.downcast_ref::<String>().unwrap().starts_with("The magic static `NOT_YET` was accessed during the initialization of `OUT_OF_ORDER`"));
+	}
+
 	println!("{magic:?} {magic} {magic:x}", magic = foo::BAR);
 	println!("{:?}", foo::MAGIC);
 
+	magic_static::init! { TEARDOWN_A };
+	unsafe { magic_static::deinit_all!() };
+	assert_eq!(*TEARDOWN.lock().unwrap(), ["A", "B"]);
+	assert!(!foo::BAR.is_initialized());
+
 	println!("Test Success");
 }
\ No newline at end of file